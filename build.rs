@@ -4,7 +4,10 @@ use toml_edit::{Item, Value};
 fn main() -> color_eyre::Result<()> {
 	color_eyre::install()?;
 
-	let env_toml = std::fs::read_to_string("env.toml").wrap_err("Couldn't read env.toml")?;
+	// configuration is loaded at runtime, so env.toml is only checked if present
+	let Ok(env_toml) = std::fs::read_to_string("env.toml") else {
+		return Ok(());
+	};
 	let env_toml: toml::Table =
 		toml::from_str(&env_toml).wrap_err("Couldn't parse env.toml as toml")?;
 
@@ -2,6 +2,8 @@
 
 use std::process::{ExitStatus, Stdio};
 
+use zeroize::Zeroizing;
+
 use crate::prelude::*;
 
 pub use git::*;
@@ -41,8 +43,14 @@ impl Command {
 		self
 	}
 
-	pub fn with_envs(mut self, envs: impl IntoIterator<Item = (&'static str, String)>) -> Self {
-		self.0.envs(envs);
+	pub fn with_envs(
+		mut self,
+		envs: impl IntoIterator<Item = (&'static str, Zeroizing<String>)>,
+	) -> Self {
+		// borrowed so the values are zeroed once copied into the command
+		for (key, value) in envs {
+			self.0.env(key, value.as_str());
+		}
 		self
 	}
 
@@ -122,8 +130,14 @@ impl AsyncCommand {
 		self
 	}
 
-	pub fn with_envs(mut self, envs: impl IntoIterator<Item = (&'static str, String)>) -> Self {
-		self.0.envs(envs);
+	pub fn with_envs(
+		mut self,
+		envs: impl IntoIterator<Item = (&'static str, Zeroizing<String>)>,
+	) -> Self {
+		// borrowed so the values are zeroed once copied into the command
+		for (key, value) in envs {
+			self.0.env(key, value.as_str());
+		}
 		self
 	}
 
@@ -12,6 +12,7 @@ use alloy_primitives::{
 };
 pub use live_logging::*;
 use tokio::sync::oneshot;
+use zeroize::Zeroizing;
 use ystd::{eyre_assert_eq, time::FutureTimeoutExt as _};
 mod live_logging;
 
@@ -22,8 +23,9 @@ pub struct Salt {
 
 #[derive(Clone, serde::Deserialize)]
 pub struct SaltConfig {
+	/// Only passed to the deno subprocess, zeroed when dropped
 	#[serde(rename = "PRIVATE_KEY")]
-	pub private_key: String,
+	pub private_key: Zeroizing<String>,
 
 	#[serde(rename = "ORCHESTRATION_NETWORK_RPC_NODE_URL")]
 	pub orchestration_network_rpc_node: Url,
@@ -56,20 +58,20 @@ impl std::fmt::Debug for SaltConfig {
 }
 
 impl SaltConfig {
-	fn iter(self) -> impl IntoIterator<Item = (&'static str, String)> {
+	fn iter(self) -> impl IntoIterator<Item = (&'static str, Zeroizing<String>)> {
 		[
 			("PRIVATE_KEY", self.private_key),
 			(
 				"ORCHESTRATION_NETWORK_RPC_NODE_URL",
-				self.orchestration_network_rpc_node.to_string(),
+				Zeroizing::new(self.orchestration_network_rpc_node.to_string()),
 			),
 			(
 				"BROADCASTING_NETWORK_RPC_NODE_URL",
-				self.broadcasting_network_rpc_node.to_string(),
+				Zeroizing::new(self.broadcasting_network_rpc_node.to_string()),
 			),
 			(
 				"BROADCASTING_NETWORK_ID",
-				self.broadcasting_network_id.to_string(),
+				Zeroizing::new(self.broadcasting_network_id.to_string()),
 			),
 		]
 	}
@@ -0,0 +1,215 @@
+use std::{collections::HashMap, time::Duration};
+
+use alloy::primitives::utils::{ParseUnits, Unit};
+use tokio::sync::mpsc;
+use twilight_http::Client;
+use twilight_model::id::{
+	Id,
+	marker::{ChannelMarker, GuildMarker, UserMarker},
+};
+use url::Url;
+
+use crate::{
+	prelude::*,
+	stats::{FaucetEvent, Outcome},
+};
+
+/// Something worth auditing, always logged and optionally posted to a discord channel
+#[derive(Debug)]
+pub enum AuditRecord {
+	Faucet {
+		event: FaucetEvent,
+		tx_url: Option<Url>,
+	},
+	/// Every admin command invocation, including refused ones
+	Admin {
+		user: Id<UserMarker>,
+		subcommand: String,
+		outcome: String,
+	},
+	/// A request refused before doing anything, by the router's middleware or the faucet's own checks
+	Refused {
+		user: Id<UserMarker>,
+		request: String,
+		reason: String,
+	},
+	/// Something the bot did by itself
+	Automatic { description: String },
+}
+
+impl std::fmt::Display for AuditRecord {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			AuditRecord::Faucet { event, tx_url } => {
+				let amount = ParseUnits::from(event.amount).format_units(Unit::ETHER);
+				write!(
+					f,
+					"`faucet` <@{}> {amount}{} ({}) to `{}`: ",
+					event.discord_id, event.token_name, event.chain_name, event.address
+				)?;
+				match (&event.outcome, tx_url) {
+					(Outcome::Success { .. }, Some(tx_url)) => write!(f, "success <{tx_url}>"),
+					(Outcome::Success { tx_hash }, None) => write!(f, "success `{tx_hash}`"),
+					(
+						Outcome::Failure {
+							correlation_id: Some(id),
+							..
+						},
+						_,
+					) => write!(f, "failed, reference `{id}`"),
+					(Outcome::Failure { .. }, _) => write!(f, "failed"),
+					(Outcome::Unknown { correlation_id }, _) => write!(
+						f,
+						"unknown, check the explorer, reference `{correlation_id}`"
+					),
+				}
+			}
+			AuditRecord::Admin {
+				user,
+				subcommand,
+				outcome,
+			} => write!(f, "`admin` <@{user}> /admin {subcommand}: {outcome}"),
+			AuditRecord::Refused {
+				user,
+				request,
+				reason,
+			} => write!(f, "`refused` <@{user}> {request}: {reason}"),
+			AuditRecord::Automatic { description } => write!(f, "`automatic` {description}"),
+		}
+	}
+}
+
+/// Posts [AuditRecord]s to each guild's audit channel from a background task per channel,
+/// batching bursts into as few messages as possible
+pub struct Audit {
+	senders: HashMap<Id<GuildMarker>, Sender>,
+}
+
+struct Sender {
+	sender: mpsc::Sender<String>,
+	/// Also posts records without a guild, which can mention other guilds
+	admin: bool,
+}
+
+impl Audit {
+	/// Discord allows 5 messages per 5 seconds per channel
+	const MIN_INTERVAL: Duration = Duration::from_millis(1500);
+	const MAX_MESSAGE_LEN: usize = 1900;
+	/// Records past this are only logged, not posted
+	const BUFFER: usize = 256;
+
+	pub fn new(
+		client: Arc<Client>,
+		channels: impl IntoIterator<Item = (Id<GuildMarker>, Id<ChannelMarker>, bool)>,
+	) -> Audit {
+		let senders = channels
+			.into_iter()
+			.map(|(guild_id, channel, admin)| {
+				let (sender, recv) = mpsc::channel(Self::BUFFER);
+				tokio::spawn(Self::sender(client.clone(), channel, recv));
+				(guild_id, Sender { sender, admin })
+			})
+			.collect();
+		Audit { senders }
+	}
+
+	/// Records without a guild, like automatic actions, are only posted to the admin guilds' audit channels
+	pub fn record(&self, guild_id: Option<Id<GuildMarker>>, record: AuditRecord) {
+		info!(target: "audit", ?guild_id, %record, "Audit record");
+		let line = record.to_string();
+		let senders = self.senders.iter().filter(|(id, sender)| match guild_id {
+			Some(guild_id) => guild_id == **id,
+			None => sender.admin,
+		});
+		for (_, Sender { sender, .. }) in senders {
+			if let Err(err) = sender.try_send(line.clone()) {
+				warn!(%err, "Couldn't queue an audit record to post");
+			}
+		}
+	}
+
+	pub fn admin(
+		&self,
+		guild_id: Option<Id<GuildMarker>>,
+		user: Id<UserMarker>,
+		subcommand: &str,
+		outcome: impl Into<String>,
+	) {
+		self.record(
+			guild_id,
+			AuditRecord::Admin {
+				user,
+				subcommand: subcommand.to_owned(),
+				outcome: outcome.into(),
+			},
+		);
+	}
+
+	pub fn refused(
+		&self,
+		guild_id: Option<Id<GuildMarker>>,
+		user: Id<UserMarker>,
+		request: impl Into<String>,
+		reason: impl Into<String>,
+	) {
+		self.record(
+			guild_id,
+			AuditRecord::Refused {
+				user,
+				request: request.into(),
+				reason: reason.into(),
+			},
+		);
+	}
+
+	pub fn automatic(&self, description: impl Into<String>) {
+		self.record(
+			None,
+			AuditRecord::Automatic {
+				description: description.into(),
+			},
+		);
+	}
+
+	async fn sender(
+		client: Arc<Client>,
+		channel: Id<ChannelMarker>,
+		mut recv: mpsc::Receiver<String>,
+	) {
+		while let Some(mut msg) = recv.recv().await {
+			// batch everything else that queued up while we were waiting
+			while let Ok(line) = recv.try_recv() {
+				if msg.len() + line.len() + 1 > Self::MAX_MESSAGE_LEN {
+					Self::post(&client, channel, &msg).await;
+					tokio::time::sleep(Self::MIN_INTERVAL).await;
+					msg.clear();
+				} else {
+					msg.push('\n');
+				}
+				msg.push_str(&line);
+			}
+			Self::post(&client, channel, &msg).await;
+			tokio::time::sleep(Self::MIN_INTERVAL).await;
+		}
+		debug!("Audit sender stopping");
+	}
+
+	async fn post(client: &Client, channel: Id<ChannelMarker>, msg: &str) {
+		let mut msg = msg.to_owned();
+		if msg.len() > Self::MAX_MESSAGE_LEN {
+			let mut end = Self::MAX_MESSAGE_LEN;
+			while !msg.is_char_boundary(end) {
+				end -= 1;
+			}
+			msg.truncate(end);
+		}
+		if let Err(err) = client
+			.create_message(channel)
+			.content(&msg)
+			.allowed_mentions(Some(&Default::default()))
+			.await
+		{
+			error!(%err, "Couldn't post to the audit channel");
+		}
+	}
+}
@@ -47,13 +47,14 @@ pub enum Cli {
 
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
-	app_tracing::install_tracing("info,salt_sdk=debug,tokens=trace").await?;
+	let env = salt_discordbot::env::Env::get().await?;
+	app_tracing::install_tracing("info,salt_sdk=debug,tokens=trace", &env.logs.dir).await?;
 	trace!("Started tokens.rs");
 
 	let cli = Cli::parse();
 
-	let env = salt_discordbot::env::Env::get().await?;
-	let signer: PrivateKeySigner = env.private_key.parse()?;
+	let private_key = env.signer.load().await?;
+	let signer: PrivateKeySigner = private_key.signer()?;
 	let me = signer.address();
 	let provider = alloy::providers::ProviderBuilder::new()
 		.wallet(signer)
@@ -99,7 +100,7 @@ async fn main() -> color_eyre::Result<()> {
 		let calldata = call.abi_encode();
 
 		let salt = salt_sdk::Salt::new(salt_sdk::SaltConfig {
-			private_key: env.private_key,
+			private_key: private_key.to_hex(),
 			orchestration_network_rpc_node: env.sepolia_arbitrum_rpc_endpoint,
 			broadcasting_network_rpc_node: env.somnia_shannon_rpc_endpoint.clone(),
 			broadcasting_network_id: 50312,
@@ -110,7 +111,9 @@ async fn main() -> color_eyre::Result<()> {
 				vault_address: env.faucet_testnet_salt_account_address,
 				recipient_address: PING,
 				data: calldata,
-				logging: &mut salt_sdk::LiveLogging::from_cb(|msg| info!(%msg, "Transaction live logs")),
+				logging: &mut salt_sdk::LiveLogging::from_cb(
+					|msg| info!(%msg, "Transaction live logs"),
+				),
 				gas: salt_sdk::GasEstimator::Mul(100.0),
 				confirm_broadcast: true,
 				auto_broadcast: true,
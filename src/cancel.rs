@@ -0,0 +1,171 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use twilight_model::id::{
+	Id,
+	marker::{InteractionMarker, UserMarker},
+};
+
+use crate::prelude::*;
+
+/// Faucets in progress by the interaction that started them.
+/// They can be cancelled from their Cancel button until they're submitted to Salt,
+/// and aren't cancelled with their interaction afterwards, see [Cancellable::is_submitted]
+#[derive(Default)]
+pub struct Cancellable(Mutex<HashMap<Id<InteractionMarker>, Pending>>);
+
+enum Pending {
+	Cancellable {
+		requester: Id<UserMarker>,
+		cancelled: bool,
+	},
+	Submitted,
+}
+
+impl Cancellable {
+	/// Cancellable until the ticket is submitted or dropped
+	pub fn register(
+		&self,
+		interaction: Id<InteractionMarker>,
+		requester: Id<UserMarker>,
+	) -> Ticket<'_> {
+		self.0.lock().or_poisoned().insert(
+			interaction,
+			Pending::Cancellable {
+				requester,
+				cancelled: false,
+			},
+		);
+		Ticket {
+			registry: self,
+			interaction,
+		}
+	}
+
+	pub fn cancel(
+		&self,
+		interaction: Id<InteractionMarker>,
+		user: Id<UserMarker>,
+	) -> Result<(), CancelErr> {
+		let mut guard = self.0.lock().or_poisoned();
+		let Some(Pending::Cancellable {
+			requester,
+			cancelled,
+		}) = guard.get_mut(&interaction)
+		else {
+			return Err(CancelErr::TooLate);
+		};
+		if *requester != user {
+			return Err(CancelErr::NotRequester(*requester));
+		}
+		info!(%interaction, %user, "Cancelling a faucet");
+		*cancelled = true;
+		Ok(())
+	}
+
+	/// Submitted faucets are left to finish when [crate::runner] stops waiting for them,
+	/// everything else is aborted
+	pub fn is_submitted(&self, interaction: Id<InteractionMarker>) -> bool {
+		matches!(
+			self.0.lock().or_poisoned().get(&interaction),
+			Some(Pending::Submitted)
+		)
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CancelErr {
+	#[error("Only <@{0}> can cancel their faucet")]
+	NotRequester(Id<UserMarker>),
+	#[error("Too late, this faucet has already been submitted to Salt")]
+	TooLate,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The faucet was cancelled")]
+pub struct Cancelled;
+
+/// Held by the faucet until it submits its Salt proposal
+#[must_use]
+pub struct Ticket<'a> {
+	registry: &'a Cancellable,
+	interaction: Id<InteractionMarker>,
+}
+
+impl<'a> Ticket<'a> {
+	/// Call right before submitting the Salt proposal, after which it can't be cancelled
+	pub fn submit(self) -> Result<Submitted<'a>, Cancelled> {
+		let mut guard = self.registry.0.lock().or_poisoned();
+		if let Some(Pending::Cancellable {
+			cancelled: true, ..
+		}) = guard.get(&self.interaction)
+		{
+			return Err(Cancelled);
+		}
+		guard.insert(self.interaction, Pending::Submitted);
+		Ok(Submitted {
+			registry: self.registry,
+			interaction: self.interaction,
+		})
+	}
+}
+
+impl Drop for Ticket<'_> {
+	fn drop(&mut self) {
+		let mut guard = self.registry.0.lock().or_poisoned();
+		if let Some(Pending::Cancellable { .. }) = guard.get(&self.interaction) {
+			guard.remove(&self.interaction);
+		}
+	}
+}
+
+/// Held by the faucet until it finishes, which it must do within [Submitted::TIMEOUT]
+/// as it isn't cancelled with its interaction
+#[must_use]
+pub struct Submitted<'a> {
+	registry: &'a Cancellable,
+	interaction: Id<InteractionMarker>,
+}
+
+impl Submitted<'_> {
+	/// Well past the interaction token's 15 minutes, so slow transactions are still reported
+	/// in the channel, see [crate::commands::follow_up_or_post]
+	pub const TIMEOUT: Duration = Duration::from_secs(60 * 30);
+}
+
+impl Drop for Submitted<'_> {
+	fn drop(&mut self) {
+		self.registry
+			.0
+			.lock()
+			.or_poisoned()
+			.remove(&self.interaction);
+	}
+}
+
+#[test]
+fn cancel_before_submit() {
+	let registry = Cancellable::default();
+	let (interaction, requester, other) = (Id::new(1), Id::new(2), Id::new(3));
+
+	let ticket = registry.register(interaction, requester);
+	assert!(matches!(
+		registry.cancel(interaction, other),
+		Err(CancelErr::NotRequester(_))
+	));
+	registry
+		.cancel(interaction, requester)
+		.expect("requester to cancel");
+	assert!(ticket.submit().is_err());
+	assert!(!registry.is_submitted(interaction));
+
+	let ticket = registry.register(interaction, requester);
+	assert!(!registry.is_submitted(interaction));
+	let submitted = ticket.submit().expect("not to be cancelled");
+	assert!(matches!(
+		registry.cancel(interaction, requester),
+		Err(CancelErr::TooLate)
+	));
+	assert!(registry.is_submitted(interaction));
+	drop(submitted);
+	assert!(!registry.is_submitted(interaction));
+}
@@ -14,6 +14,7 @@ use crate::{
 };
 
 pub mod explorer;
+pub mod proof;
 
 pub trait BlockchainListing {
 	fn chain_id(&self) -> u64;
@@ -40,6 +41,26 @@ pub(super) enum SupportedChain {
 	PolygonAmoy(PolygonAmoy),
 }
 
+impl SupportedChain {
+	/// Every supported chain without a recipient address, for listing purposes
+	pub fn all() -> [SupportedChain; 4] {
+		[
+			SupportedChain::SomniaShannon(SomniaShannon {
+				address: String::new(),
+			}),
+			SupportedChain::SepoliaEtherium(SepoliaEthereum {
+				address: String::new(),
+			}),
+			SupportedChain::SepoliaArbitrum(SepoliaArbitrum {
+				address: String::new(),
+			}),
+			SupportedChain::PolygonAmoy(PolygonAmoy {
+				address: String::new(),
+			}),
+		]
+	}
+}
+
 impl BlockchainListing for SupportedChain {
 	fn chain_id(&self) -> u64 {
 		match self {
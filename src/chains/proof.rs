@@ -0,0 +1,140 @@
+use alloy::{
+	primitives::{
+		Address, U256,
+		utils::{ParseUnits, Unit, parse_ether},
+	},
+	providers::{Provider as _, ProviderBuilder},
+};
+use url::Url;
+
+use crate::prelude::*;
+
+/// Optionally requires recipients to have some real activity on a "proof" chain,
+/// usually a mainnet, before they can be fauceted testnet tokens.
+/// Passing either the balance or nonce requirement is enough
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ProofOfActivity {
+	/// Displayed to users, e.g. "Ethereum mainnet"
+	pub chain_name: String,
+	/// Can point to a local anvil node for testing
+	pub rpc_endpoint: Url,
+	/// In ether units, e.g. "0.001"
+	#[serde(default, deserialize_with = "deserialize_ether")]
+	pub min_balance: Option<U256>,
+	#[serde(default)]
+	pub min_nonce: Option<u64>,
+}
+
+fn deserialize_ether<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	use serde::Deserialize as _;
+	let Some(ether) = Option::<String>::deserialize(deserializer)? else {
+		return Ok(None);
+	};
+	parse_ether(&ether)
+		.map(Some)
+		.map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProofErr {
+	#[error(
+		"Your wallet address doesn't have enough activity on {chain_name} to use the faucet.\n{requirements}"
+	)]
+	Inactive {
+		chain_name: String,
+		requirements: String,
+	},
+
+	#[error("Couldn't check your wallet's activity on {chain_name}, please try again later")]
+	Rpc {
+		chain_name: String,
+		#[source]
+		err: color_eyre::Report,
+	},
+}
+
+impl ProofOfActivity {
+	pub async fn check(&self, address: Address) -> Result<(), ProofErr> {
+		let rpc_err = |err: color_eyre::Report| ProofErr::Rpc {
+			chain_name: self.chain_name.clone(),
+			err,
+		};
+		let provider = ProviderBuilder::new()
+			.connect(self.rpc_endpoint.as_str())
+			.await
+			.wrap_err("Couldn't connect to proof of activity RPC")
+			.map_err(rpc_err)?;
+
+		if let Some(min_balance) = self.min_balance {
+			let balance = provider
+				.get_balance(address)
+				.await
+				.wrap_err("Couldn't get balance")
+				.map_err(rpc_err)?;
+			if balance >= min_balance {
+				return Ok(());
+			}
+		}
+
+		if let Some(min_nonce) = self.min_nonce {
+			let nonce = provider
+				.get_transaction_count(address)
+				.await
+				.wrap_err("Couldn't get nonce")
+				.map_err(rpc_err)?;
+			if nonce >= min_nonce {
+				return Ok(());
+			}
+		}
+
+		if self.min_balance.is_none() && self.min_nonce.is_none() {
+			return Ok(());
+		}
+
+		Err(ProofErr::Inactive {
+			chain_name: self.chain_name.clone(),
+			requirements: self.describe(),
+		})
+	}
+
+	fn describe(&self) -> String {
+		let balance = self.min_balance.map(|min_balance| {
+			format!(
+				"a balance of at least {}",
+				ParseUnits::from(min_balance).format_units(Unit::ETHER)
+			)
+		});
+		let nonce = self
+			.min_nonce
+			.map(|min_nonce| format!("at least {min_nonce} sent transaction(s)"));
+		let requirements = [balance, nonce]
+			.into_iter()
+			.flatten()
+			.collect::<Vec<_>>()
+			.join(" or ");
+		format!("Required: {requirements}")
+	}
+}
+
+#[tokio::test]
+#[ignore = "requires anvil in PATH"]
+async fn proof_of_activity_anvil() {
+	let anvil = alloy::node_bindings::Anvil::new().spawn();
+	let proof = ProofOfActivity {
+		chain_name: "anvil".into(),
+		rpc_endpoint: anvil.endpoint_url(),
+		min_balance: Some(parse_ether("1").unwrap()),
+		min_nonce: Some(1),
+	};
+
+	// anvil's default accounts are pre-funded
+	proof.check(anvil.addresses()[0]).await.expect("funded");
+	assert!(matches!(
+		proof.check(Address::repeat_byte(0x42)).await,
+		Err(ProofErr::Inactive { .. })
+	));
+}
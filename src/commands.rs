@@ -1,29 +1,69 @@
+use std::sync::LazyLock;
+
 use crate::{
 	common::{GlobalState, GlobalStateRef},
 	prelude::*,
 };
-use twilight_interactions::command::CreateCommand;
+use router::{ComponentRoute, Middleware, Route, Router, Scope};
 use twilight_model::{
-	application::interaction::{Interaction, application_command::CommandData},
+	application::interaction::{
+		Interaction, application_command::CommandData,
+		message_component::MessageComponentInteractionData,
+	},
+	channel::message::{
+		AllowedMentions, Component, MessageFlags,
+		component::{ActionRow, Button, ButtonStyle},
+	},
 	http::interaction::{InteractionResponse, InteractionResponseType},
+	id::{Id, marker::UserMarker},
 };
 use twilight_util::builder::InteractionResponseDataBuilder;
 
+const FAUCET_MIDDLEWARE: &[Middleware] = &[
+	Middleware::GuildOnly(
+		"The faucet can only be used from within a server, not in DMs, because it depends on your server roles and membership",
+	),
+	Middleware::FaucetChannel,
+	Middleware::SpamFilter {
+		except: &["status"],
+	},
+];
+
+/// Context menus are allowed for whoever can use `/admin ratelimits`
+const RATELIMITS_ADMIN: &[Middleware] = &[Middleware::AdminAs("ratelimits")];
+
+/// Every command and button, adding one only needs a [router::Route] or [router::ComponentRoute] here
+static ROUTER: LazyLock<Router> = LazyLock::new(|| {
+	Router::new(
+		[
+			Route::new::<faucet::FaucetCommand>(Scope::Global, FAUCET_MIDDLEWARE),
+			Route::autocompleted::<faucet::UnifiedFaucetCommand>(Scope::Global, FAUCET_MIDDLEWARE),
+			Route::new::<admin::AdminCommand>(Scope::AdminGuilds, &[Middleware::Admin]),
+			Route::new::<standard::SomniaStandardCommand>(Scope::AdminGuilds, &[]),
+			Route::context_menu::<menus::FaucetHistory>(Scope::AdminGuilds, RATELIMITS_ADMIN),
+			Route::context_menu::<menus::ResetRatelimits>(Scope::AdminGuilds, RATELIMITS_ADMIN),
+			Route::context_menu::<menus::BlockFromFaucet>(Scope::AdminGuilds, RATELIMITS_ADMIN),
+			Route::context_menu::<menus::RetryThisFaucet>(Scope::AdminGuilds, RATELIMITS_ADMIN),
+			Route::context_menu::<menus::ShowTxDetails>(
+				Scope::AdminGuilds,
+				&[Middleware::AdminAs("trace")],
+			),
+		],
+		[
+			ComponentRoute::new::<faucet::Retry>(FAUCET_MIDDLEWARE),
+			// the faucet being cancelled holds the spam filter
+			ComponentRoute::new::<faucet::Cancel>(&[]),
+		],
+	)
+});
+
 pub fn public_commands() -> Vec<twilight_model::application::command::Command> {
-	[faucet::FaucetCommand::create_command().into()]
-		.into_iter()
-		.collect()
+	ROUTER.commands(Scope::Global)
 }
 
-/// Includes all of [public_commands] as well
+/// Only registered in [crate::env::Env::admin_guilds]
 pub fn admin_commands() -> Vec<twilight_model::application::command::Command> {
-	// let mut public = public_commands();
-	let mut public = vec![];
-	public.extend([
-		admin::AdminCommand::create_command().into(),
-		standard::SomniaStandardCommand::create_command().into(),
-	]);
-	public
+	ROUTER.commands(Scope::AdminGuilds)
 }
 
 /// Handle a command interaction.
@@ -33,22 +73,54 @@ pub async fn handle_command(
 	data: CommandData,
 ) -> Result<()> {
 	trace!("Handling command interaction: {:#?}", interaction);
-	match &*data.name {
-		// "orders-list" => orders::OrdersListCommand::handle(state.clone(), interaction, data).await,
-		"salt-faucet" => faucet::FaucetCommand::handle(state.get(), interaction, data).await,
-		"admin" => admin::AdminCommand::handle(state.get(), interaction, data).await,
-		"somnia-standard" => {
-			standard::SomniaStandardCommand::handle(state.get(), interaction, data).await
-		}
-		name => bail!("unknown command: {}", name),
-	}
+	ROUTER.dispatch(state, interaction, data).await
+}
+
+/// Suggest option values while the user is typing a command
+pub async fn handle_autocomplete(
+	state: GlobalState,
+	interaction: Interaction,
+	data: CommandData,
+) -> Result<()> {
+	ROUTER.autocomplete(state.get(), interaction, data).await
+}
+
+/// Handle a button press
+pub async fn handle_component(
+	state: GlobalState,
+	interaction: Interaction,
+	data: MessageComponentInteractionData,
+) -> Result<()> {
+	trace!("Handling component interaction: {:#?}", interaction);
+	ROUTER.press(state, interaction, &data.custom_id).await
+}
+
+/// The part of a button's custom ID before its arguments, for logs and metrics
+pub fn component_name(custom_id: &str) -> &str {
+	custom_id
+		.split_once(':')
+		.map_or(custom_id, |(prefix, _)| prefix)
 }
 
 mod admin;
 mod faucet;
+mod menus;
+mod router;
 mod standard;
 
+pub(crate) use faucet::vault_balances;
+
 async fn defer(state: GlobalStateRef<'_>, interaction: &Interaction) -> color_eyre::Result<()> {
+	defer_as(state, interaction, Visibility::Public).await
+}
+
+/// Shows the bot as thinking until [edit_response_with] replaces it,
+/// for when the first reply could miss discord's 3 second deadline
+pub(crate) async fn defer_as(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	visibility: Visibility,
+) -> color_eyre::Result<()> {
 	state
 		.client
 		.interaction(interaction.application_id)
@@ -57,7 +129,11 @@ async fn defer(state: GlobalStateRef<'_>, interaction: &Interaction) -> color_ey
 			&interaction.token,
 			&InteractionResponse {
 				kind: InteractionResponseType::DeferredChannelMessageWithSource,
-				data: None,
+				data: Some(
+					InteractionResponseDataBuilder::new()
+						.flags(visibility.flags())
+						.build(),
+				),
 			},
 		)
 		.await
@@ -65,10 +141,73 @@ async fn defer(state: GlobalStateRef<'_>, interaction: &Interaction) -> color_ey
 		.map(|_| ())
 }
 
+/// Replaces the deferred or initial response, keeping its visibility
+pub(crate) async fn edit_response_with(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	msg: impl AsRef<str>,
+	buttons: impl IntoIterator<Item = Button>,
+) -> color_eyre::Result<()> {
+	state
+		.client
+		.interaction(interaction.application_id)
+		.update_response(&interaction.token)
+		.content(Some(msg.as_ref()))
+		.components(Some(&row(buttons)))
+		.await
+		.wrap_err("Couldn't edit the response to a discord interaction")
+		.map(|_| ())
+}
+
+/// Whether replies are only visible to the user who invoked the command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Visibility {
+	Public,
+	Ephemeral,
+}
+
+impl Visibility {
+	fn flags(self) -> MessageFlags {
+		match self {
+			Visibility::Public => MessageFlags::empty(),
+			Visibility::Ephemeral => MessageFlags::EPHEMERAL,
+		}
+	}
+}
+
 async fn respond(
 	state: GlobalStateRef<'_>,
 	interaction: &Interaction,
 	msg: impl Into<String>,
+) -> color_eyre::Result<()> {
+	respond_as(state, interaction, Visibility::Public, msg).await
+}
+
+/// Only visible to the user who invoked the command
+async fn respond_ephemeral(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	msg: impl Into<String>,
+) -> color_eyre::Result<()> {
+	respond_as(state, interaction, Visibility::Ephemeral, msg).await
+}
+
+pub(crate) async fn respond_as(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	visibility: Visibility,
+	msg: impl Into<String>,
+) -> color_eyre::Result<()> {
+	respond_with(state, interaction, visibility, msg, []).await
+}
+
+/// With a row of buttons
+pub(crate) async fn respond_with(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	visibility: Visibility,
+	msg: impl Into<String>,
+	buttons: impl IntoIterator<Item = Button>,
 ) -> color_eyre::Result<()> {
 	state
 		.client
@@ -78,7 +217,13 @@ async fn respond(
 			&interaction.token,
 			&InteractionResponse {
 				kind: InteractionResponseType::ChannelMessageWithSource,
-				data: Some(InteractionResponseDataBuilder::new().content(msg).build()),
+				data: Some(
+					InteractionResponseDataBuilder::new()
+						.content(msg)
+						.flags(visibility.flags())
+						.components(row(buttons))
+						.build(),
+				),
 			},
 		)
 		.await
@@ -90,13 +235,222 @@ async fn follow_up(
 	state: GlobalStateRef<'_>,
 	interaction: &Interaction,
 	msg: impl AsRef<str>,
+) -> color_eyre::Result<()> {
+	follow_up_as(state, interaction, Visibility::Public, msg).await
+}
+
+pub(crate) async fn follow_up_as(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	visibility: Visibility,
+	msg: impl AsRef<str>,
+) -> color_eyre::Result<()> {
+	follow_up_with(state, interaction, visibility, msg, []).await
+}
+
+/// With a row of buttons
+pub(crate) async fn follow_up_with(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	visibility: Visibility,
+	msg: impl AsRef<str>,
+	buttons: impl IntoIterator<Item = Button>,
 ) -> color_eyre::Result<()> {
 	state
 		.client
 		.interaction(interaction.application_id)
 		.create_followup(&interaction.token)
 		.content(msg.as_ref())
+		.flags(visibility.flags())
+		.components(&row(buttons))
 		.await
 		.wrap_err("Couldn't followup a discord interaction")
 		.map(|_| ())
 }
+
+/// Discord interaction tokens expire 15 minutes after the interaction,
+/// this leaves a minute for slow requests and clock skew
+const FOLLOW_UP_WINDOW: time::Duration = time::Duration::minutes(14);
+
+/// Whether the interaction's token can no longer be relied on to follow up
+fn token_expiring(interaction: &Interaction) -> bool {
+	let age =
+		time::OffsetDateTime::now_utc() - crate::eligibility::snowflake_created(interaction.id);
+	age >= FOLLOW_UP_WINDOW
+}
+
+/// The same as [follow_up_with] until the interaction's token is about to expire.
+/// Then public replies are posted in the channel mentioning `user`, and ephemeral ones
+/// are DMed to them, falling back to a mention without any of the message.
+/// Returns how the message was actually shown
+pub(crate) async fn follow_up_or_post(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	visibility: Visibility,
+	user: Id<UserMarker>,
+	msg: impl AsRef<str>,
+	buttons: impl IntoIterator<Item = Button>,
+) -> color_eyre::Result<Visibility> {
+	if !token_expiring(interaction) {
+		follow_up_with(state, interaction, visibility, msg, buttons).await?;
+		return Ok(visibility);
+	}
+	info!("Interaction token is about to expire, posting the reply elsewhere");
+	if visibility == Visibility::Ephemeral {
+		// only links, as other buttons are refused outside of servers
+		let links = buttons.into_iter().filter(|button| button.url.is_some());
+		match direct_message(state, user, msg.as_ref(), links).await {
+			Ok(()) => return Ok(Visibility::Ephemeral),
+			Err(err) => warn!(
+				?err,
+				"Couldn't DM an ephemeral reply, mentioning them instead"
+			),
+		}
+		let redacted = channel_post(visibility, user, msg.as_ref());
+		post_in_channel(state, interaction, user, &redacted, []).await?;
+		return Ok(Visibility::Ephemeral);
+	}
+	let content = channel_post(visibility, user, msg.as_ref());
+	post_in_channel(state, interaction, user, &content, buttons).await?;
+	Ok(Visibility::Public)
+}
+
+/// Ephemeral replies are redacted, so only the user's mention and where to look is public
+fn channel_post(visibility: Visibility, user: Id<UserMarker>, msg: &str) -> String {
+	match visibility {
+		Visibility::Public if msg.starts_with(&format!("<@{user}>")) => msg.to_owned(),
+		Visibility::Public => format!("<@{user}> {msg}"),
+		Visibility::Ephemeral => format!(
+			"<@{user}> your faucet has an update but I couldn't DM you, see `/salt-faucet status`"
+		),
+	}
+}
+
+async fn direct_message(
+	state: GlobalStateRef<'_>,
+	user: Id<UserMarker>,
+	msg: &str,
+	buttons: impl IntoIterator<Item = Button>,
+) -> color_eyre::Result<()> {
+	let channel = state
+		.client
+		.create_private_channel(user)
+		.await
+		.wrap_err("Couldn't open a DM")?
+		.model()
+		.await
+		.wrap_err("Couldn't parse the DM channel")?;
+	state
+		.client
+		.create_message(channel.id)
+		.content(msg)
+		.components(&row(buttons))
+		.await
+		.wrap_err("Couldn't send a DM")?;
+	Ok(())
+}
+
+async fn post_in_channel(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	user: Id<UserMarker>,
+	content: &str,
+	buttons: impl IntoIterator<Item = Button>,
+) -> color_eyre::Result<()> {
+	let channel = interaction
+		.channel
+		.as_ref()
+		.ok_or_else(|| eyre!("Interaction without a channel to post in"))?;
+	state
+		.client
+		.create_message(channel.id)
+		.content(content)
+		.components(&row(buttons))
+		.allowed_mentions(Some(&AllowedMentions {
+			users: vec![user],
+			..Default::default()
+		}))
+		.await
+		.wrap_err("Couldn't post in the interaction's channel")?;
+	Ok(())
+}
+
+/// Nothing if there are no buttons, discord refuses empty rows
+fn row(buttons: impl IntoIterator<Item = Button>) -> Vec<Component> {
+	let components = buttons
+		.into_iter()
+		.map(Component::Button)
+		.collect::<Vec<_>>();
+	if components.is_empty() {
+		return Vec::new();
+	}
+	vec![Component::ActionRow(ActionRow { components })]
+}
+
+/// Presses are handled by a [router::Press]
+pub(crate) fn button(custom_id: String, label: &str, style: ButtonStyle) -> Button {
+	Button {
+		custom_id: Some(custom_id),
+		disabled: false,
+		emoji: None,
+		label: Some(label.to_owned()),
+		style,
+		url: None,
+		sku_id: None,
+	}
+}
+
+/// Opens the url, discord doesn't tell the bot when it's pressed
+pub(crate) fn link_button(url: &url::Url, label: &str) -> Button {
+	Button {
+		custom_id: None,
+		disabled: false,
+		emoji: None,
+		label: Some(label.to_owned()),
+		style: ButtonStyle::Link,
+		url: Some(url.to_string()),
+		sku_id: None,
+	}
+}
+
+#[test]
+fn command_names_are_unique() {
+	let mut names: Vec<_> = [Scope::Global, Scope::AdminGuilds]
+		.into_iter()
+		.flat_map(|scope| ROUTER.commands(scope))
+		.map(|command| command.name)
+		.collect();
+	let len = names.len();
+	names.sort();
+	names.dedup();
+	assert_eq!(names.len(), len, "{names:?}");
+	assert!(
+		public_commands()
+			.iter()
+			.any(|command| command.name == "salt-faucet")
+	);
+	assert!(
+		admin_commands()
+			.iter()
+			.any(|command| command.name == "admin")
+	);
+
+	let mut prefixes: Vec<_> = ROUTER.components.iter().map(|route| route.prefix).collect();
+	let len = prefixes.len();
+	prefixes.sort();
+	prefixes.dedup();
+	assert_eq!(prefixes.len(), len, "{prefixes:?}");
+}
+
+#[test]
+fn ephemeral_replies_are_redacted_in_the_channel() {
+	let user = Id::new(1234);
+	let msg = "Error transacting 0.1STT (Somnia Shannon) to 0xabc: reverted";
+	let public = channel_post(Visibility::Public, user, msg);
+	assert_eq!(public, format!("<@1234> {msg}"));
+	assert_eq!(channel_post(Visibility::Public, user, &public), public);
+	let ephemeral = channel_post(Visibility::Ephemeral, user, msg);
+	assert!(ephemeral.starts_with("<@1234> "));
+	assert!(!ephemeral.contains("0xabc"));
+	assert!(!ephemeral.contains("reverted"));
+}
@@ -1,13 +1,13 @@
 use std::{sync::atomic::Ordering, time::Duration};
 
 use crate::{
-	commands::{defer, respond},
+	commands::{defer, follow_up, respond, router::Handle},
 	prelude::*,
 };
 use color_eyre::{Section, eyre::Context as _};
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::{
-	application::interaction::{Interaction, application_command::CommandData},
+	application::interaction::Interaction,
 	http::{
 		attachment::{self, Attachment},
 		interaction::{InteractionResponse, InteractionResponseType},
@@ -17,6 +17,8 @@ use twilight_util::builder::InteractionResponseDataBuilder;
 
 use crate::common::GlobalStateRef;
 
+/// Visible to everyone in the admin guilds, so the configured [crate::permissions::AdminPermissions]
+/// decide who can use it rather than discord's ADMINISTRATOR permission
 #[derive(Debug, Clone, CommandModel, CreateCommand)]
 #[command(name = "admin", desc = "Admin commands for the Salt discord bot")]
 pub(super) enum AdminCommand {
@@ -29,23 +31,37 @@ pub(super) enum AdminCommand {
 	#[command(name = "purge-user-ratelimits")]
 	PurgeUserRatelimits(PurgeUserRatelimits),
 
+	#[command(name = "ratelimits")]
+	Ratelimits(ratelimits::RatelimitsCommand),
+
+	#[command(name = "stats")]
+	Stats(stats::Stats),
+
 	#[command(name = "dump-logs")]
-	DumpLogs(DumpLogs),
+	DumpLogs(dump_logs::DumpLogs),
+
+	#[command(name = "trace")]
+	Trace(trace::Trace),
+
+	#[command(name = "reload-config")]
+	ReloadConfig(ReloadConfig),
 
 	#[command(name = "kill")]
 	Kill(Kill),
 }
 
-impl AdminCommand {
-	pub async fn handle(
+mod dump_logs;
+pub(super) mod ratelimits;
+mod stats;
+mod trace;
+
+impl Handle for AdminCommand {
+	async fn handle(
+		self,
 		state: GlobalStateRef<'_>,
 		interaction: Interaction,
-		data: CommandData,
 	) -> color_eyre::Result<()> {
-		let command =
-			AdminCommand::from_interaction(data.into()).wrap_err("Couldn't parse command data")?;
-
-		match command {
+		match self {
 			AdminCommand::DumpUserDedupe(_) => {
 				let dump = state.per_user_spam_filters.dump();
 				state
@@ -84,10 +100,26 @@ impl AdminCommand {
 				cmd.handle(state, interaction).await?;
 				Ok(())
 			}
+			AdminCommand::Ratelimits(cmd) => {
+				cmd.handle(state, interaction).await?;
+				Ok(())
+			}
+			AdminCommand::Stats(cmd) => {
+				cmd.handle(state, interaction).await?;
+				Ok(())
+			}
 			AdminCommand::DumpLogs(cmd) => {
 				cmd.handle(state, interaction).await?;
 				Ok(())
 			}
+			AdminCommand::Trace(cmd) => {
+				cmd.handle(state, interaction).await?;
+				Ok(())
+			}
+			AdminCommand::ReloadConfig(cmd) => {
+				cmd.handle(state, interaction).await?;
+				Ok(())
+			}
 			AdminCommand::Kill(cmd) => {
 				cmd.handle(state, interaction).await;
 				Ok(())
@@ -108,74 +140,17 @@ pub(super) struct DumpUserDedupe;
 pub(super) struct PurgeUserDedupe;
 
 /// Purse all user ratelimit data, which will allow people to request slightly more
-/// than normal
+/// than normal.
+/// Prefer `/admin ratelimits reset` to unblock a single person
 #[derive(Debug, Clone, CommandModel, CreateCommand)]
 #[command(name = "purge-user-ratelimits")]
 pub(super) struct PurgeUserRatelimits;
 
-/// Dumps today's logs as a file
+/// Validates and swaps in the config file without restarting,
+/// requests already in progress finish with the old config
 #[derive(Debug, Clone, CommandModel, CreateCommand)]
-#[command(name = "dump-logs")]
-pub(super) struct DumpLogs;
-
-impl DumpLogs {
-	pub async fn handle(
-		&self,
-		state: GlobalStateRef<'_>,
-		interaction: Interaction,
-	) -> color_eyre::Result<()> {
-		defer(state, &interaction).await?;
-
-		match self.get_file().await {
-			Ok(file) => {
-				state
-					.client
-					.interaction(interaction.application_id)
-					.create_followup(&interaction.token)
-					.attachments(&[file])
-					.await
-					.wrap_err("Couldn't send attached logs file")?;
-				Ok(())
-			}
-			Err(err) => {
-				error!(%err, "Internal error while dumping logs");
-				state
-					.client
-					.interaction(interaction.application_id)
-					.create_followup(&interaction.token)
-					.content(&format!(
-						"An internal error occurred while dumping the logs: {err}"
-					))
-					.await?;
-				Ok(())
-			}
-		}
-	}
-
-	async fn get_file(&self) -> color_eyre::Result<Attachment> {
-		let now = time::OffsetDateTime::now_utc();
-		let format = time::macros::format_description!("[year]-[month]-[day]");
-		let file_timestamp = now.format(&format)?;
-		let file_name = format!("{}.{}", crate::app_tracing::PREFIX, file_timestamp);
-		let file_path = Utf8PathBuf::from(crate::app_tracing::LOGS_DIR).join(&file_name);
-
-		file_path
-			.assert_file()
-			.await
-			.wrap_err("Logs file not found")?;
-
-		let data = ystd::fs::read(&file_path)
-			.await
-			.wrap_err("Couldn't read log file")?;
-		let attachment = Attachment {
-			description: Some(format!("Log file exported at {now}")),
-			file: data,
-			filename: format!("rust-discordbot-{file_timestamp}.json"),
-			id: 1,
-		};
-		Ok(attachment)
-	}
-}
+#[command(name = "reload-config")]
+pub(super) struct ReloadConfig;
 
 /// Stops the discordbot, hopefully taking it offline cleanly
 #[derive(Debug, Clone, CommandModel, CreateCommand)]
@@ -193,6 +168,25 @@ impl Kill {
 	}
 }
 
+impl ReloadConfig {
+	pub async fn handle(
+		&self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> color_eyre::Result<()> {
+		defer(state, &interaction).await?;
+		let msg = match state.config.reload().await {
+			Ok(summary) => summary,
+			Err(err) => {
+				// the full error can quote lines of the config file, which may contain secrets
+				error!(?err, "Couldn't reload config");
+				format!("{err}, check the logs for why")
+			}
+		};
+		follow_up(state, &interaction, msg).await
+	}
+}
+
 impl PurgeUserRatelimits {
 	pub async fn handle(
 		&self,
@@ -0,0 +1,224 @@
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::{application::interaction::Interaction, http::attachment::Attachment};
+
+use crate::{
+	commands::{defer, follow_up},
+	common::GlobalStateRef,
+	prelude::*,
+};
+
+/// Dumps the logs between two days as files
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "dump-logs")]
+pub(in crate::commands) struct DumpLogs {
+	/// First day to include as YYYY-MM-DD, defaults to today
+	from: Option<String>,
+	/// Last day to include as YYYY-MM-DD, defaults to the first day
+	to: Option<String>,
+	/// Only include logs at least this severe, defaults to everything
+	level: Option<LogLevel>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, CommandOption, CreateOption)]
+pub(in crate::commands) enum LogLevel {
+	#[option(name = "trace", value = "TRACE")]
+	Trace,
+	#[option(name = "debug", value = "DEBUG")]
+	Debug,
+	#[option(name = "info", value = "INFO")]
+	Info,
+	#[option(name = "warn", value = "WARN")]
+	Warn,
+	#[option(name = "error", value = "ERROR")]
+	Error,
+}
+
+impl LogLevel {
+	/// From the `level` of a JSON log line
+	fn parse(level: &str) -> Option<LogLevel> {
+		Some(match level {
+			"TRACE" => LogLevel::Trace,
+			"DEBUG" => LogLevel::Debug,
+			"INFO" => LogLevel::Info,
+			"WARN" => LogLevel::Warn,
+			"ERROR" => LogLevel::Error,
+			_ => return None,
+		})
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Couldn't parse {0:?} as a YYYY-MM-DD date")]
+struct InvalidDateErr(String);
+
+impl DumpLogs {
+	/// Discord's upload limit is 10MiB
+	const MAX_ATTACHMENT_BYTES: usize = 9 * 1024 * 1024;
+	/// Everything is decompressed into memory
+	const MAX_DAYS: i64 = 31;
+
+	pub async fn handle(
+		&self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> color_eyre::Result<()> {
+		defer(state, &interaction).await?;
+
+		let (from, to) = match self.range() {
+			Ok(range) => range,
+			Err(err) => return follow_up(state, &interaction, err.to_string()).await,
+		};
+		if to < from || (to - from).whole_days() >= Self::MAX_DAYS {
+			return follow_up(
+				state,
+				&interaction,
+				format!(
+					"Please pick a range of between 1 and {} days, with `from` before `to`",
+					Self::MAX_DAYS
+				),
+			)
+			.await;
+		}
+
+		let attachments = match self.get_files(state, from, to).await {
+			Ok(attachments) => attachments,
+			Err(err) => {
+				error!(%err, "Internal error while dumping logs");
+				return follow_up(
+					state,
+					&interaction,
+					format!("An internal error occurred while dumping the logs: {err}"),
+				)
+				.await;
+			}
+		};
+		if attachments.is_empty() {
+			return follow_up(state, &interaction, "No logs were found for those days").await;
+		}
+
+		// one attachment per message to stay under the upload limit
+		let parts = attachments.len();
+		for (i, attachment) in attachments.into_iter().enumerate() {
+			state
+				.client
+				.interaction(interaction.application_id)
+				.create_followup(&interaction.token)
+				.content(&format!("Logs part {}/{parts}", i + 1))
+				.attachments(&[attachment])
+				.await
+				.wrap_err("Couldn't send attached logs file")?;
+		}
+		Ok(())
+	}
+
+	fn range(&self) -> Result<(time::Date, time::Date), InvalidDateErr> {
+		let format = time::macros::format_description!("[year]-[month]-[day]");
+		let parse = |date: &str| {
+			time::Date::parse(date.trim(), &format).map_err(|_| InvalidDateErr(date.to_owned()))
+		};
+		let from = match &self.from {
+			Some(from) => parse(from)?,
+			None => time::OffsetDateTime::now_utc().date(),
+		};
+		let to = match &self.to {
+			Some(to) => parse(to)?,
+			None => from,
+		};
+		Ok((from, to))
+	}
+
+	async fn get_files(
+		&self,
+		state: GlobalStateRef<'_>,
+		from: time::Date,
+		to: time::Date,
+	) -> color_eyre::Result<Vec<Attachment>> {
+		let now = time::OffsetDateTime::now_utc();
+		let files = state.env.logs.files_between(from, to).await?;
+		let (Some(first), Some(last)) = (files.first(), files.last()) else {
+			return Ok(Vec::new());
+		};
+		let name = format!(
+			"rust-discordbot-{}-{}",
+			first.date_string(),
+			last.date_string()
+		);
+
+		let mut data = Vec::new();
+		for file in &files {
+			let contents = file.read().await?;
+			match self.level {
+				None => data.extend_from_slice(&contents),
+				Some(level) => {
+					for line in contents.split_inclusive(|byte| *byte == b'\n') {
+						if line_level(line).is_none_or(|line_level| line_level >= level) {
+							data.extend_from_slice(line);
+						}
+					}
+				}
+			}
+		}
+
+		let chunks = split_lines(&data, Self::MAX_ATTACHMENT_BYTES);
+		let parts = chunks.len();
+		Ok(chunks
+			.into_iter()
+			.enumerate()
+			.map(|(i, chunk)| Attachment {
+				description: Some(format!("Log file exported at {now}")),
+				file: chunk.to_vec(),
+				filename: if parts == 1 {
+					format!("{name}.json")
+				} else {
+					format!("{name}-part{}.json", i + 1)
+				},
+				id: 1,
+			})
+			.collect())
+	}
+}
+
+/// Lines that aren't JSON are kept, as they may be part of a multiline message
+fn line_level(line: &[u8]) -> Option<LogLevel> {
+	#[derive(serde::Deserialize)]
+	struct Line {
+		level: String,
+	}
+	let line: Line = serde_json::from_slice(line).ok()?;
+	LogLevel::parse(&line.level)
+}
+
+/// Splits on line boundaries where possible, a single line longer than `max` is split anywhere
+fn split_lines(data: &[u8], max: usize) -> Vec<&[u8]> {
+	let mut chunks = Vec::new();
+	let mut rest = data;
+	while rest.len() > max {
+		let end = rest[..max]
+			.iter()
+			.rposition(|byte| *byte == b'\n')
+			.map(|newline| newline + 1)
+			.unwrap_or(max);
+		let (chunk, remaining) = rest.split_at(end);
+		chunks.push(chunk);
+		rest = remaining;
+	}
+	if !rest.is_empty() {
+		chunks.push(rest);
+	}
+	chunks
+}
+
+#[test]
+fn dump_logs_splitting() {
+	let data = b"{\"level\":\"INFO\"}\n{\"level\":\"ERROR\"}\n";
+	assert_eq!(split_lines(data, 1000), vec![&data[..]]);
+	assert_eq!(
+		split_lines(data, 25),
+		vec![
+			&b"{\"level\":\"INFO\"}\n"[..],
+			&b"{\"level\":\"ERROR\"}\n"[..]
+		]
+	);
+	assert_eq!(line_level(b"{\"level\":\"WARN\"}\n"), Some(LogLevel::Warn));
+	assert!(LogLevel::Error > LogLevel::Info);
+}
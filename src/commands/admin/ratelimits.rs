@@ -0,0 +1,177 @@
+use alloy::primitives::Address;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+	application::interaction::Interaction,
+	id::{Id, marker::UserMarker},
+};
+
+use crate::{commands::respond, common::GlobalStateRef, prelude::*, ratelimits::Target};
+
+/// Inspect and manage the ratelimits of individual users
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "ratelimits")]
+pub(in crate::commands) enum RatelimitsCommand {
+	#[command(name = "show")]
+	Show(Show),
+
+	#[command(name = "reset")]
+	Reset(Reset),
+
+	#[command(name = "grant")]
+	Grant(Grant),
+
+	#[command(name = "block")]
+	Block(Block),
+
+	#[command(name = "unblock")]
+	Unblock(Unblock),
+}
+
+/// Show the faucet history and ratelimit status of a user or address
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "show")]
+pub(in crate::commands) struct Show {
+	/// The discord user to inspect
+	user: Option<Id<UserMarker>>,
+	/// The wallet address to inspect
+	address: Option<String>,
+}
+
+/// Reset the ratelimits of one user and the addresses they claimed to, or of one address
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "reset")]
+pub(in crate::commands) struct Reset {
+	/// The discord user to reset, along with the addresses they claimed to
+	user: Option<Id<UserMarker>>,
+	/// The wallet address to reset
+	address: Option<String>,
+	/// Only reset this chain ID, defaults to every chain
+	#[command(min_value = 1)]
+	chain: Option<i64>,
+}
+
+/// Grant a user extra faucets, usable on any chain once they reach their normal limits
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "grant")]
+pub(in crate::commands) struct Grant {
+	/// The discord user to grant faucets to
+	user: Id<UserMarker>,
+	/// How many extra faucets to grant
+	#[command(min_value = 1, max_value = 100)]
+	count: i64,
+}
+
+/// Block a user from the faucet, on top of the configured denylist
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "block")]
+pub(in crate::commands) struct Block {
+	/// The discord user to block
+	user: Id<UserMarker>,
+}
+
+/// Let a blocked user use the faucet again
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "unblock")]
+pub(in crate::commands) struct Unblock {
+	/// The discord user to unblock
+	user: Id<UserMarker>,
+}
+
+/// Exactly one of `user` or `address` must be provided
+fn target(user: Option<Id<UserMarker>>, address: Option<&str>) -> Result<Target, String> {
+	match (user, address) {
+		(Some(user), None) => Ok(Target::User(user)),
+		(None, Some(address)) => address
+			.parse::<Address>()
+			.map(Target::Address)
+			.map_err(|err| format!("Invalid Etherium wallet address {address:?}: {err}")),
+		_ => Err("Please provide exactly one of `user` or `address`".into()),
+	}
+}
+
+impl RatelimitsCommand {
+	pub async fn handle(
+		&self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> color_eyre::Result<()> {
+		let msg = match self {
+			RatelimitsCommand::Show(cmd) => match target(cmd.user, cmd.address.as_deref()) {
+				Ok(target) => state.ratelimits.lock().await?.describe(target),
+				Err(msg) => msg,
+			},
+			RatelimitsCommand::Reset(cmd) => match target(cmd.user, cmd.address.as_deref()) {
+				Ok(target) => {
+					let chain_id = cmd.chain.map(|chain| chain as u64);
+					let removed = state
+						.ratelimits
+						.lock()
+						.await?
+						.reset(target, chain_id)
+						.await?;
+					let whose = match target {
+						Target::User(_) => format!("{target} and the addresses they claimed to"),
+						Target::Address(_) => target.to_string(),
+					};
+					match chain_id {
+						Some(chain_id) => format!(
+							"Reset the ratelimits of {whose} on chain ID {chain_id}, removing {removed} record(s)"
+						),
+						None => format!(
+							"Reset the ratelimits of {whose} on every chain, removing {removed} record(s)"
+						),
+					}
+				}
+				Err(msg) => msg,
+			},
+			RatelimitsCommand::Grant(cmd) => {
+				let total = state
+					.ratelimits
+					.lock()
+					.await?
+					.grant(cmd.user, cmd.count as u32)
+					.await?;
+				format!(
+					"Granted <@{}> {} extra faucet(s), they now have {total} available",
+					cmd.user, cmd.count
+				)
+			}
+			RatelimitsCommand::Block(cmd) => block(state, cmd.user).await?,
+			RatelimitsCommand::Unblock(cmd) => {
+				match state.ratelimits.lock().await?.unblock(cmd.user).await? {
+					true => format!("Unblocked <@{}> from the faucet", cmd.user),
+					false => format!("<@{}> wasn't blocked from the faucet", cmd.user),
+				}
+			}
+		};
+		respond(state, &interaction, truncate(msg)).await
+	}
+}
+
+/// Shared with the "Block from faucet" context menu
+pub(in crate::commands) async fn block(
+	state: GlobalStateRef<'_>,
+	user: Id<UserMarker>,
+) -> color_eyre::Result<String> {
+	let msg = match state.ratelimits.lock().await?.block(user).await? {
+		true => {
+			format!("Blocked <@{user}> from the faucet, `/admin ratelimits unblock` undoes this")
+		}
+		false => format!("<@{user}> is already blocked from the faucet"),
+	};
+	Ok(msg)
+}
+
+/// Discord rejects messages over 2000 characters
+pub(in crate::commands) fn truncate(mut msg: String) -> String {
+	const MAX: usize = 1900;
+	if msg.len() > MAX {
+		let mut end = MAX;
+		while !msg.is_char_boundary(end) {
+			end -= 1;
+		}
+		msg.truncate(end);
+		msg.push_str("...<truncated>");
+	}
+	msg
+}
@@ -0,0 +1,69 @@
+use alloy::primitives::utils::{ParseUnits, Unit};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{application::interaction::Interaction, http::attachment::Attachment};
+
+use crate::{
+	commands::defer,
+	common::GlobalStateRef,
+	prelude::*,
+	stats::{self, FaucetEvents},
+};
+
+/// Faucet statistics per chain and token, with daily data attached as CSV
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "stats")]
+pub(in crate::commands) struct Stats {
+	/// How many days back to include, defaults to 7
+	#[command(min_value = 1, max_value = 365)]
+	days: Option<i64>,
+}
+
+impl Stats {
+	pub async fn handle(
+		&self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> color_eyre::Result<()> {
+		defer(state, &interaction).await?;
+
+		let days = self.days.unwrap_or(7);
+		let now = time::OffsetDateTime::now_utc();
+		let events = FaucetEvents::read_since(now - time::Duration::days(days)).await?;
+		let daily = stats::aggregate(&events);
+
+		let mut msg = format!("**Faucet stats for the last {days} day(s)**\n");
+		if daily.is_empty() {
+			msg.push_str("No faucets were attempted in this period\n");
+		}
+		for ((chain_name, token_name), stats) in stats::totals(&daily) {
+			msg.push_str(&format!(
+				"- {chain_name} {token_name}: {} {token_name} given out, {} attempt(s), {:.1}% failed, {} unique user(s), {} unique address(es), median latency {}\n",
+				ParseUnits::from(stats.volume).format_units(Unit::ETHER),
+				stats.attempts,
+				stats.failure_rate() * 100.0,
+				stats.users.len(),
+				stats.addresses.len(),
+				stats
+					.median_latency()
+					.map(|latency| format!("{:.1}s", latency.as_secs_f64()))
+					.unwrap_or_else(|| "unknown".into()),
+			));
+		}
+
+		let attachment = Attachment {
+			description: Some(format!("Daily faucet stats exported at {now}")),
+			file: stats::to_csv(&daily).into_bytes(),
+			filename: format!("faucet-stats-{days}d.csv"),
+			id: 1,
+		};
+		state
+			.client
+			.interaction(interaction.application_id)
+			.create_followup(&interaction.token)
+			.content(&msg)
+			.attachments(&[attachment])
+			.await
+			.wrap_err("Couldn't send faucet stats")?;
+		Ok(())
+	}
+}
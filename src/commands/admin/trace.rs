@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use alloy::primitives::TxHash;
+use serde_json::Value;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+	application::interaction::Interaction,
+	http::attachment::Attachment,
+	id::{
+		Id,
+		marker::{InteractionMarker, UserMarker},
+	},
+};
+
+use crate::{
+	commands::{defer, follow_up},
+	common::GlobalStateRef,
+	logs::LogsConfig,
+	prelude::*,
+	user_errors::CorrelationId,
+};
+
+/// Everything logged while handling an interaction, as a span tree attachment
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "trace")]
+pub(in crate::commands) struct Trace {
+	/// An interaction id, error reference, transaction hash or user mention
+	query: String,
+	/// How many days of logs to search, defaults to 7
+	#[command(min_value = 1, max_value = 90)]
+	days: Option<i64>,
+}
+
+/// Which interactions to trace, matched against the fields of the `interaction` span
+/// created in [crate::runner::process_interactions]
+#[derive(Debug, PartialEq)]
+enum Query {
+	Interaction(Id<InteractionMarker>),
+	Correlation(CorrelationId),
+	TxHash(TxHash),
+	User(Id<UserMarker>),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+	"Couldn't understand {0:?}, expected an interaction id, a 16 character error reference, a 0x transaction hash or a user mention"
+)]
+struct InvalidQueryErr(String);
+
+impl std::str::FromStr for Query {
+	type Err = InvalidQueryErr;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		let invalid = || InvalidQueryErr(s.to_owned());
+		if let Some(mention) = s.strip_prefix("<@").and_then(|s| s.strip_suffix('>')) {
+			let id = mention.trim_start_matches('!');
+			return id.parse().map(Query::User).map_err(|_| invalid());
+		}
+		if s.starts_with("0x") {
+			return s.parse().map(Query::TxHash).map_err(|_| invalid());
+		}
+		if s.len() == 16
+			&& let Ok(id) = s.parse()
+		{
+			return Ok(Query::Correlation(id));
+		}
+		s.parse().map(Query::Interaction).map_err(|_| invalid())
+	}
+}
+
+impl std::fmt::Display for Query {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Query::Interaction(id) => write!(f, "interaction {id}"),
+			Query::Correlation(id) => write!(f, "reference `{id}`"),
+			Query::TxHash(hash) => write!(f, "transaction `{hash}`"),
+			Query::User(id) => write!(f, "<@{id}>"),
+		}
+	}
+}
+
+impl Query {
+	fn matches(&self, span: &serde_json::Map<String, Value>) -> bool {
+		let (name, expected) = match self {
+			Query::Interaction(id) => ("interaction_id", id.to_string()),
+			Query::Correlation(id) => ("correlation_id", id.to_string()),
+			Query::TxHash(hash) => ("tx_hash", hash.to_string()),
+			Query::User(id) => ("user", id.to_string()),
+		};
+		span.get(name).and_then(Value::as_str) == Some(&expected)
+	}
+}
+
+/// The outermost `interaction` span of a JSON log line
+fn interaction_span(line: &Value) -> Option<&serde_json::Map<String, Value>> {
+	line.get("spans")?
+		.as_array()?
+		.iter()
+		.find(|span| span.get("name").and_then(Value::as_str) == Some("interaction"))?
+		.as_object()
+}
+
+impl Trace {
+	/// Users can have a lot of interactions, only the latest are traced
+	const MAX_INTERACTIONS: usize = 10;
+	/// Discord's upload limit is 10MiB
+	const MAX_ATTACHMENT_BYTES: usize = 9 * 1024 * 1024;
+
+	pub async fn handle(
+		&self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> color_eyre::Result<()> {
+		defer(state, &interaction).await?;
+
+		let query: Query = match self.query.parse() {
+			Ok(query) => query,
+			Err(err) => return follow_up(state, &interaction, err.to_string()).await,
+		};
+		let days = self.days.unwrap_or(7);
+		let since = time::OffsetDateTime::now_utc().date() - time::Duration::days(days);
+
+		let traces = search(&state.env.logs, &query, since).await?;
+		if traces.is_empty() {
+			return follow_up(
+				state,
+				&interaction,
+				format!("Nothing was logged for {query} in the last {days} day(s)"),
+			)
+			.await;
+		}
+
+		let skipped = traces.len().saturating_sub(Self::MAX_INTERACTIONS);
+		let mut file = String::new();
+		for (interaction_id, lines) in traces.iter().skip(skipped) {
+			file.push_str(&format!("# interaction {interaction_id}\n"));
+			for line in lines {
+				file.push_str(&render(line));
+				file.push('\n');
+			}
+			file.push('\n');
+		}
+		if file.len() > Self::MAX_ATTACHMENT_BYTES {
+			let mut end = Self::MAX_ATTACHMENT_BYTES;
+			while !file.is_char_boundary(end) {
+				end -= 1;
+			}
+			file.truncate(end);
+			file.push_str("\n...<truncated>");
+		}
+
+		let mut msg = format!(
+			"Found {} interaction(s) for {query} in the last {days} day(s)",
+			traces.len()
+		);
+		if skipped > 0 {
+			msg.push_str(&format!(
+				", only the latest {} are attached",
+				Self::MAX_INTERACTIONS
+			));
+		}
+		let attachment = Attachment {
+			description: Some(format!("Trace of {query}")),
+			file: file.into_bytes(),
+			filename: "trace.txt".into(),
+			id: 1,
+		};
+		state
+			.client
+			.interaction(interaction.application_id)
+			.create_followup(&interaction.token)
+			.content(&msg)
+			.attachments(&[attachment])
+			.await
+			.wrap_err("Couldn't send trace")?;
+		Ok(())
+	}
+}
+
+/// Every log line of each matching interaction, oldest interaction first.
+/// Searches the daily JSON log files from `since` onwards
+async fn search(
+	logs: &LogsConfig,
+	query: &Query,
+	since: time::Date,
+) -> color_eyre::Result<Vec<(String, Vec<Value>)>> {
+	let today = time::OffsetDateTime::now_utc().date();
+	let files = logs.files_between(since, today).await?;
+
+	let mut order = Vec::new();
+	let mut lines: HashMap<String, Vec<Value>> = HashMap::new();
+	let mut matched = Vec::new();
+	for file in files {
+		let file = file.read().await?;
+		for line in String::from_utf8_lossy(&file).lines() {
+			let Ok(line) = serde_json::from_str::<Value>(line) else {
+				continue;
+			};
+			let Some(span) = interaction_span(&line) else {
+				continue;
+			};
+			let Some(interaction_id) = span.get("interaction_id").and_then(Value::as_str) else {
+				continue;
+			};
+			let interaction_id = interaction_id.to_owned();
+			if query.matches(span) && !matched.contains(&interaction_id) {
+				matched.push(interaction_id.clone());
+			}
+			lines
+				.entry(interaction_id.clone())
+				.or_insert_with(|| {
+					order.push(interaction_id);
+					Vec::new()
+				})
+				.push(line);
+		}
+	}
+
+	Ok(order
+		.into_iter()
+		.filter(|interaction_id| matched.contains(interaction_id))
+		.map(|interaction_id| {
+			let lines = lines.remove(&interaction_id).unwrap_or_default();
+			(interaction_id, lines)
+		})
+		.collect())
+}
+
+/// One line per event, indented by span depth
+fn render(line: &Value) -> String {
+	let str = |key: &str| line.get(key).and_then(Value::as_str).unwrap_or_default();
+	let spans = line
+		.get("spans")
+		.and_then(Value::as_array)
+		.map(Vec::as_slice)
+		.unwrap_or_default();
+	let path = spans
+		.iter()
+		.filter_map(|span| span.get("name").and_then(Value::as_str))
+		.collect::<Vec<_>>()
+		.join(" > ");
+	let indent = "  ".repeat(spans.len().saturating_sub(1));
+	let mut rendered = format!(
+		"{} {:>5} {indent}[{path}] {}:",
+		str("timestamp"),
+		str("level"),
+		str("target")
+	);
+	if let Some(fields) = line.get("fields").and_then(Value::as_object) {
+		if let Some(message) = fields.get("message").and_then(Value::as_str) {
+			rendered.push(' ');
+			rendered.push_str(message);
+		}
+		for (key, value) in fields.iter().filter(|(key, _)| *key != "message") {
+			rendered.push_str(&format!(" {key}={value}"));
+		}
+	}
+	rendered
+}
+
+#[test]
+fn trace_queries() {
+	assert_eq!(
+		"<@!1234>".parse::<Query>().expect("to parse"),
+		Query::User(Id::new(1234))
+	);
+	assert_eq!(
+		"0000000000ab12cd".parse::<Query>().expect("to parse"),
+		Query::Correlation("0000000000ab12cd".parse().expect("to parse"))
+	);
+	assert_eq!(
+		"1371363785985490975".parse::<Query>().expect("to parse"),
+		Query::Interaction(Id::new(1371363785985490975))
+	);
+	assert!("0x1234".parse::<Query>().is_err());
+
+	let line: Value = serde_json::from_str(
+		r#"{"timestamp":"2025-06-01T00:00:00Z","level":"INFO","fields":{"message":"Hi"},"target":"t","spans":[{"name":"interaction","interaction_id":"1371363785985490975","user":"1234"}]}"#,
+	)
+	.expect("to deserialize");
+	let span = interaction_span(&line).expect("to have an interaction span");
+	assert!(Query::User(Id::new(1234)).matches(span));
+	assert!(!Query::User(Id::new(4321)).matches(span));
+}
@@ -1,22 +1,42 @@
-use std::{sync::Mutex, time::Duration};
+use std::{
+	sync::Mutex,
+	time::{Duration, Instant},
+};
 
 use crate::chains::explorer::ExplorableBlockchain as _;
-use crate::commands::{follow_up, respond};
+use crate::commands::{
+	Visibility, defer_as, edit_response_with, follow_up_or_post, link_button, respond_as,
+	respond_ephemeral, router::Handle,
+};
 use crate::{
-	chains::{self, BlockchainListing, SupportedChain, explorer::BlockchainExplorer as _},
+	audit::AuditRecord,
+	cancel::Submitted,
+	chains::{self, BlockchainListing, SupportedChain, explorer::BlockchainExplorer},
+	eligibility::{Applicant, IneligibleErr},
+	env::Env,
 	prelude::*,
 	ratelimits::Key,
+	stats::{FaucetEvent, FaucetEvents, Outcome},
+	user_errors::{CorrelationId, UserFacingErr},
 };
-use alloy::primitives::utils::{ParseUnits, Unit};
+use alloy::primitives::{
+	U256,
+	utils::{ParseUnits, Unit},
+};
+use alloy::providers::Provider as _;
 use chains::NativeFaucet as _;
-use color_eyre::Section;
-use salt_sdk::{Salt, SaltConfig, TransactionInfo};
+use salt_sdk::{Salt, SaltConfig, TransactionDone, TransactionInfo};
+use time::OffsetDateTime;
 use tokio::sync::mpsc::Receiver;
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::{
-	application::interaction::{Interaction, application_command::CommandData},
+	application::interaction::Interaction,
 	http::interaction::{InteractionResponse, InteractionResponseType},
-	id::{Id, marker::UserMarker},
+	id::{
+		Id,
+		marker::{GuildMarker, RoleMarker, UserMarker},
+	},
+	util::Timestamp,
 };
 use twilight_util::builder::InteractionResponseDataBuilder;
 
@@ -42,23 +62,51 @@ pub(super) enum FaucetCommand {
 
 	#[command(name = "somnia-shannon-ping")]
 	PingSomniaShannon(erc20::SomniaShannonPing),
-	// #[command(name = "check")]
-	// Check(Check),
 
-	// #[command(name = "ratelimits")]
-	// Ratelimits(CheckRatelimits),
+	#[command(name = "status")]
+	Status(status::Status),
 }
 
+mod buttons;
 mod erc20;
+mod status;
+mod unified;
+
+pub(super) use buttons::{Cancel, Retry};
+pub(super) use unified::UnifiedFaucetCommand;
 
 pub struct DiscordInfo {
 	discord_id: Id<UserMarker>,
+	/// Who can cancel the faucet, an admin retrying it on [DiscordInfo::discord_id]'s behalf
+	requester: Id<UserMarker>,
 	has_expanded_limits: bool,
+	joined_at: Option<Timestamp>,
+	roles: Vec<Id<RoleMarker>>,
+	replies: Replies,
+}
+
+/// How to reply to the user, from the guild's [crate::guilds::Privacy] settings
+#[derive(Debug, Clone, Copy)]
+pub struct Replies {
+	visibility: Visibility,
+	/// Publish the final success message publicly even if [Replies::visibility] is ephemeral
+	public_success: bool,
+}
+
+impl DiscordInfo {
+	fn applicant(&self) -> Applicant<'_> {
+		Applicant {
+			discord_id: self.discord_id,
+			joined_at: self.joined_at,
+			roles: &self.roles,
+		}
+	}
 }
 
 async fn discord_info(
 	state: GlobalStateRef<'_>,
 	interaction: &Interaction,
+	default_visibility: Visibility,
 ) -> color_eyre::Result<DiscordInfo> {
 	let member = match &interaction.member {
 		Some(user) => user,
@@ -98,98 +146,304 @@ async fn discord_info(
 			bail!("Must be provided a user ID");
 		}
 	};
-	let expanded_limits_id = Id::new(1364832034677198949);
-	let has_expanded_limits = member.roles.contains(&expanded_limits_id);
+	let guild = state.env.guild(interaction.guild_id);
+	let has_expanded_limits = guild.is_some_and(|guild| guild.has_expanded_limits(&member.roles));
+	let privacy = guild.map(|guild| guild.privacy).unwrap_or_default();
+	let replies = Replies {
+		visibility: privacy.visibility(default_visibility),
+		public_success: privacy.public_success,
+	};
 	let discord_id = user.id;
 	Ok(DiscordInfo {
 		discord_id,
+		requester: discord_id,
 		has_expanded_limits,
+		joined_at: member.joined_at,
+		roles: member.roles.clone(),
+		replies,
 	})
 }
 
 impl FaucetCommand {
-	pub async fn handle(
+	/// Can be overridden per guild by [crate::guilds::Privacy::ephemeral]
+	fn default_visibility(&self) -> Visibility {
+		match self {
+			FaucetCommand::Status(_) => Visibility::Ephemeral,
+			_ => Visibility::Public,
+		}
+	}
+}
+
+impl Handle for FaucetCommand {
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		let discord_info = discord_info(state, &interaction, self.default_visibility()).await?;
+		self.faucet(state, interaction, discord_info, true).await
+	}
+}
+
+/// What [FaucetCommand::faucet] gives out once the checks pass
+enum Faucet {
+	Native(SupportedChain),
+	Ping(erc20::SomniaShannonPing),
+}
+
+impl Faucet {
+	/// For auditing refusals
+	fn request(&self) -> String {
+		match self {
+			Faucet::Native(chain) => {
+				format!("{} ({})", chain.native_token_name(), chain.chain_name())
+			}
+			Faucet::Ping(token) => format!(
+				"{} ({})",
+				erc20::SomniaShannonPing::erc20_token_name(),
+				token.chain_name()
+			),
+		}
+	}
+}
+
+/// Refusals are audited along with the faucets that reach Salt, see [record_event]
+fn audit_refusal(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	discord_id: Id<UserMarker>,
+	request: String,
+	reason: impl Into<String>,
+) {
+	state.audit.refused(
+		interaction.guild_id,
+		discord_id,
+		format!("faucet {request}"),
+		reason,
+	);
+}
+
+impl FaucetCommand {
+	/// For an admin retrying someone else's faucet, which is ratelimited as theirs.
+	/// Admins vouch for their eligibility, but blocked users are still refused
+	pub(super) async fn handle_on_behalf_of(
+		self,
 		state: GlobalStateRef<'_>,
 		interaction: Interaction,
-		data: CommandData,
-	) -> color_eyre::Result<()> {
-		let command =
-			FaucetCommand::from_interaction(data.into()).wrap_err("Couldn't parse command data")?;
+		discord_id: Id<UserMarker>,
+	) -> Result<()> {
+		// the context menu's route doesn't engage the spam filter, and would only for the admin
+		let Ok(_spam_guard) = state.per_user_spam_filters.engage(discord_id) else {
+			state.metrics.spam_filter_rejections.inc();
+			let request = "retried by an admin".to_owned();
+			audit_refusal(state, &interaction, discord_id, request, "in progress");
+			let msg = format!("<@{discord_id}> already has a faucet in progress");
+			return respond_ephemeral(state, &interaction, msg).await;
+		};
+		let discord_info = discord_info(state, &interaction, self.default_visibility()).await?;
+		let discord_info = DiscordInfo {
+			discord_id,
+			has_expanded_limits: false,
+			joined_at: None,
+			roles: Vec::new(),
+			..discord_info
+		};
+		self.faucet(state, interaction, discord_info, false).await
+	}
 
-		let discord_info = discord_info(state, &interaction).await?;
-		let res = state.per_user_spam_filters.engage(discord_info.discord_id);
-		let _guard;
-		match res {
-			Err(err) => {
-				let data = InteractionResponseDataBuilder::new()
-					.content(err.to_string())
-					.build();
-				let response = InteractionResponse {
-					kind: InteractionResponseType::ChannelMessageWithSource,
-					data: Some(data),
-				};
-				state
-					.client
-					.interaction(interaction.application_id)
-					.create_response(interaction.id, &interaction.token, &response)
-					.await?;
-				return Ok(());
+	async fn faucet(
+		self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+		discord_info: DiscordInfo,
+		check_eligibility: bool,
+	) -> Result<()> {
+		let faucet = match self {
+			// not a faucet, so none of the checks apply
+			FaucetCommand::Status(status) => {
+				return status.handle(state, interaction, discord_info).await;
 			}
-			Ok(guard) => {
-				_guard = guard;
+			FaucetCommand::PolygonAmoy(chain) => Faucet::Native(SupportedChain::PolygonAmoy(chain)),
+			FaucetCommand::SepoliaArbitrum(chain) => {
+				Faucet::Native(SupportedChain::SepoliaArbitrum(chain))
 			}
-		};
-
-		let res = {
-			let state = state.reborrow();
-			let interaction = interaction.clone();
-			match command {
-				// FaucetCommand::Check(check) => check.handle(state, interaction).await,
-				// FaucetCommand::Ratelimits(ratelimits) => {
-				// 	ratelimits.handle(state, interaction).await
-				// }
-				FaucetCommand::PolygonAmoy(chain) => {
-					SupportedChain::PolygonAmoy(chain)
-						.handle(state, interaction, discord_info)
-						.await
-				}
-				FaucetCommand::SepoliaArbitrum(chain) => {
-					SupportedChain::SepoliaArbitrum(chain)
-						.handle(state, interaction, discord_info)
-						.await
-				}
-				FaucetCommand::SepoliaEtherium(chain) => {
-					SupportedChain::SepoliaEtherium(chain)
-						.handle(state, interaction, discord_info)
-						.await
-				}
-				FaucetCommand::SomniaShannon(chain) => {
-					SupportedChain::SomniaShannon(chain)
-						.handle(state, interaction, discord_info)
-						.await
-				}
-				FaucetCommand::PingSomniaShannon(token) => {
-					token.handle(state, interaction, discord_info).await
-				}
+			FaucetCommand::SepoliaEtherium(chain) => {
+				Faucet::Native(SupportedChain::SepoliaEtherium(chain))
 			}
+			FaucetCommand::SomniaShannon(chain) => {
+				Faucet::Native(SupportedChain::SomniaShannon(chain))
+			}
+			FaucetCommand::PingSomniaShannon(token) => Faucet::Ping(token),
 		};
-		// global internal error handler
-		// this is a best effort attempt, you must manually handle all user facing errors in .handle
-		if let Err(err) = res {
-			error!(%err, ?err, "An internal error occurred");
-			state
-				.client
-				.interaction(interaction.application_id)
-				.create_followup(&interaction.token)
-				.content(&format!("An internal error occurred: {err}"))
-				.await
-				.wrap_err("Couldn't send internal error message")
-				.note(format!("Original internal error: {err}"))?;
+		let visibility = discord_info.replies.visibility;
+		let discord_id = discord_info.discord_id;
+		let Some(_in_flight) = state.drain.enter() else {
+			audit_refusal(
+				state,
+				&interaction,
+				discord_id,
+				faucet.request(),
+				"restarting",
+			);
+			respond_as(
+				state,
+				&interaction,
+				visibility,
+				"The faucet is restarting, please try again in a few minutes",
+			)
+			.await?;
+			return Ok(());
+		};
+		if check_eligibility
+			&& let Err(err) = state.env.eligibility.check(&discord_info.applicant())
+		{
+			info!(%discord_id, %err, "Refusing an ineligible user");
+			audit_refusal(
+				state,
+				&interaction,
+				discord_id,
+				faucet.request(),
+				"ineligible",
+			);
+			respond_as(state, &interaction, visibility, err.to_string()).await?;
+			return Ok(());
+		}
+		if state.ratelimits.lock().await?.is_blocked(discord_id) {
+			info!(%discord_id, "Refusing a blocked user");
+			audit_refusal(state, &interaction, discord_id, faucet.request(), "blocked");
+			let msg = IneligibleErr::Denylisted.to_string();
+			respond_as(state, &interaction, visibility, msg).await?;
+			return Ok(());
+		}
+
+		match faucet {
+			Faucet::Native(chain) => chain.handle(state, interaction, discord_info).await,
+			Faucet::Ping(token) => token.handle(state, interaction, discord_info).await,
 		}
-		Ok(())
 	}
 }
 
+/// The faucet vault's balance of every token it gives out, for [crate::metrics::Metrics]
+pub(crate) async fn vault_balances(
+	env: &Env,
+) -> Vec<(&'static str, &'static str, color_eyre::Result<U256>)> {
+	let mut balances = Vec::new();
+	for chain in SupportedChain::all() {
+		let balance = async {
+			let provider = alloy::providers::ProviderBuilder::new()
+				.connect(chain.rpc_url(env).as_str())
+				.await?;
+			let balance = provider
+				.get_balance(env.faucet_testnet_salt_account_address)
+				.await?;
+			color_eyre::Result::<U256>::Ok(balance)
+		}
+		.await;
+		balances.push((chain.chain_name(), chain.native_token_name(), balance));
+		if matches!(chain, SupportedChain::SomniaShannon(_)) {
+			balances.push((
+				chain.chain_name(),
+				erc20::SomniaShannonPing::erc20_token_name(),
+				erc20::SomniaShannonPing::vault_balance(env).await,
+			));
+		}
+	}
+	balances
+}
+
+/// How a faucet submitted to Salt ended
+enum Submission {
+	Done(Result<TransactionDone, salt_sdk::Error>),
+	/// Still running after [Submitted::TIMEOUT], the transaction may yet be broadcast
+	TimedOut,
+}
+
+/// Submitted faucets aren't cancelled along with their interaction, so they're limited to
+/// [Submitted::TIMEOUT] instead
+async fn time_limited(
+	transaction: impl Future<Output = (Result<TransactionDone, salt_sdk::Error>, Result<()>)>,
+) -> (Submission, Result<()>) {
+	match tokio::time::timeout(Submitted::TIMEOUT, transaction).await {
+		Ok((res, logging)) => (Submission::Done(res), logging),
+		Err(_) => {
+			warn!(timeout = ?Submitted::TIMEOUT, "Giving up on a faucet submitted to Salt");
+			(Submission::TimedOut, Ok(()))
+		}
+	}
+}
+
+/// Ratelimited like a success, as the transaction may still have been sent,
+/// and without a Retry button for the same reason
+async fn report_unknown(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	visibility: Visibility,
+	key: &Key,
+	faucet: &str,
+) -> Result<()> {
+	state
+		.ratelimits
+		.lock()
+		.await?
+		.register(key, None)
+		.await
+		.wrap_err("Couldn't register a faucet with an unknown outcome")?;
+	let msg = format!(
+		"Gave up waiting for the faucet of {faucet} after {} minutes, it may still be sent so please check the explorer before trying again (reference `{}`)",
+		Submitted::TIMEOUT.as_secs() / 60,
+		CorrelationId::of(interaction.id)
+	);
+	follow_up_or_post(state, interaction, visibility, key.discord_id, msg, [])
+		.await
+		.wrap_err("Couldn't follow up on a faucet with an unknown outcome")?;
+	Ok(())
+}
+
+/// Best effort, failing to record an event shouldn't fail the faucet.
+/// Also audits the attempt
+async fn record_event(
+	state: GlobalStateRef<'_>,
+	guild_id: Option<Id<GuildMarker>>,
+	correlation_id: CorrelationId,
+	key: &Key,
+	amount: U256,
+	res: &Submission,
+	started: Instant,
+	explorer: &impl BlockchainExplorer<SupportedChain>,
+) {
+	let outcome = match res {
+		Submission::Done(Ok(data)) => Outcome::Success { tx_hash: data.hash },
+		Submission::Done(Err(err)) => Outcome::Failure {
+			reason: err.to_string(),
+			correlation_id: Some(correlation_id),
+		},
+		Submission::TimedOut => Outcome::Unknown { correlation_id },
+	};
+	state
+		.metrics
+		.faucet(&key.chain_name, &key.token_name, &outcome);
+	let event = FaucetEvent {
+		at: OffsetDateTime::now_utc(),
+		discord_id: key.discord_id,
+		address: key.address,
+		chain_id: key.chain_id,
+		chain_name: key.chain_name.to_owned(),
+		token_name: key.token_name.to_owned(),
+		amount,
+		outcome,
+		latency: started.elapsed(),
+	};
+	if let Err(err) = FaucetEvents::record(&event).await {
+		error!(%err, ?event, "Couldn't record faucet event");
+	}
+	let tx_url = match res {
+		Submission::Done(Ok(data)) => {
+			tracing::Span::current().record("tx_hash", tracing::field::display(data.hash));
+			explorer.transaction_explorer_url(data.hash).ok()
+		}
+		_ => None,
+	};
+	state
+		.audit
+		.record(guild_id, AuditRecord::Faucet { event, tx_url });
+}
+
 // todo: take GlobalState Arc, make logging a static fut with tokio::spawn,
 // add some helper string methods like truncate_lossy
 impl SupportedChain {
@@ -208,15 +462,22 @@ impl SupportedChain {
 		let address = self.address();
 		let DiscordInfo {
 			discord_id,
+			requester,
 			has_expanded_limits,
+			replies,
+			..
 		} = discord_info;
+		let visibility = replies.visibility;
 
+		let request = format!("{token_name} ({chain_name})");
 		let address = match address {
 			Ok(address) => address,
 			Err(err) => {
-				respond(
+				audit_refusal(state, &interaction, discord_id, request, "invalid address");
+				respond_as(
 					state,
 					&interaction,
+					visibility,
 					&format!(
 						"Invalid Etherium wallet address {:?}: {}",
 						self.address_str(),
@@ -228,19 +489,43 @@ impl SupportedChain {
 			}
 		};
 
+		// the RPC calls below can take longer than discord waits for a response
+		defer_as(state, &interaction, visibility).await?;
+
+		// check the recipient has real activity elsewhere
+		if let Some(proof) = &state.env.proof_of_activity
+			&& let Err(err) = proof.check(address).await
+		{
+			warn!(%address, ?err, "Recipient failed the proof of activity check");
+			let request = format!("{request} to `{address}`");
+			audit_refusal(
+				state,
+				&interaction,
+				discord_id,
+				request,
+				"no proof of activity",
+			);
+			edit_response_with(state, &interaction, err.to_string(), []).await?;
+			return Ok(());
+		}
+
 		// check ratelimiting if not expanded limits
 		let ratelimit_key = Key {
 			address,
 			discord_id,
 			chain_id,
 			chain_name,
+			token_name,
 		};
 		if !has_expanded_limits {
 			let ratelimit = state.ratelimits.lock().await?.check(&ratelimit_key);
 			if let Err(msg) = ratelimit {
+				state.metrics.ratelimit_refusal(chain_name, token_name);
+				let request = format!("{request} to `{address}`");
+				audit_refusal(state, &interaction, discord_id, request, "ratelimited");
 				let msg =
 					format!("Couldn't faucet you any tokens because you are ratelimited!\n{msg}");
-				respond(state, &interaction, &msg).await?;
+				edit_response_with(state, &interaction, &msg, []).await?;
 				return Ok(());
 			}
 		} else {
@@ -248,23 +533,32 @@ impl SupportedChain {
 		}
 
 		// initial response
-		respond(
+		let ticket = state.cancellable.register(interaction.id, requester);
+		edit_response_with(
 			state,
 			&interaction,
-			&format!("Starting faucet of {amount_eth}{token_name} ({chain_name}) to {address} ..."),
+			format!("Starting faucet of {amount_eth}{token_name} ({chain_name}) to {address} ..."),
+			[Cancel {
+				interaction: interaction.id,
+			}
+			.button()],
 		)
 		.await?;
+		let started = std::time::Instant::now();
 
 		// do transaction
 		let (send_logs, mut recv_logs) = tokio::sync::mpsc::channel(10);
 		let mut live_logging = salt_sdk::LiveLogging::from_sender(send_logs);
 		let salt_config = SaltConfig {
-			private_key: state.env.private_key.clone(),
+			private_key: state.private_key.to_hex(),
 			orchestration_network_rpc_node: state.env.sepolia_arbitrum_rpc_endpoint.clone(),
 			broadcasting_network_rpc_node: rpc_url,
 			broadcasting_network_id: chain_id,
 		};
 		let salt = Salt::new(salt_config)?;
+		let Some(_submitted) = buttons::submit(state, &interaction, ticket).await else {
+			return Ok(());
+		};
 		let transaction_task = salt.transaction(TransactionInfo {
 			amount,
 			vault_address: state.env.faucet_testnet_salt_account_address,
@@ -278,66 +572,95 @@ impl SupportedChain {
 		});
 		let tx_running_logging_task = async {
 			let mut recv_logs: Receiver<_> = recv_logs;
+			let mut last = started;
 			while let Some(log) = recv_logs.recv().await {
 				info!(%log, "Sending live log");
+				state.metrics.salt_stage(&log, &mut last);
 				// no need to clobber discord with useless logs
 				if matches!(log, salt_sdk::Log::BroadcastedTx(_)) {
 					return Ok(());
 				}
-				follow_up(state, &interaction, log.to_string())
-					.await
-					.wrap_err("Live logging failed to send")?;
+				follow_up_or_post(
+					state,
+					&interaction,
+					visibility,
+					discord_id,
+					log.to_string(),
+					[],
+				)
+				.await
+				.wrap_err("Live logging failed to send")?;
 			}
 			Result::<(), color_eyre::Report>::Ok(())
 		};
 
-		let (res, logging_err) = tokio::join!(transaction_task, tx_running_logging_task);
+		let (res, logging_err) =
+			time_limited(async { tokio::join!(transaction_task, tx_running_logging_task) }).await;
 
 		if let Err(err) = logging_err {
 			error!("Failed to send live logs:\n{}", err);
 		}
 
-		match res {
-			Err(err) => {
-				error!("Failed to do salt transaction:\n{}", err);
-				let mut err_string = err.to_string();
-
-				if let salt_sdk::Error::SubprocessExitedBadlyWithOutput(output) = err {
-					err_string = output.stderr;
-				}
+		record_event(
+			state,
+			interaction.guild_id,
+			CorrelationId::of(interaction.id),
+			&ratelimit_key,
+			amount,
+			&res,
+			started,
+			&self.block_explorer(),
+		)
+		.await;
 
-				if err_string.len() > 1900 {
-					// only keeps first 1900 bytes, avoiding a panic if using String.split_off
-					// https://doc.rust-lang.org/stable/std/string/struct.String.html#method.split_off
-					let truncated = err_string
-						.into_bytes()
-						.into_iter()
-						.take(1900)
-						.collect::<Vec<u8>>();
-					let truncated = String::from_utf8_lossy(&truncated);
-					err_string = format!("{truncated}...<truncated>");
-				}
-				err_string = format!(
-					"Error transacting {amount_eth}{token_name} ({chain_name}) to {address}:\n{err_string}"
+		match res {
+			Submission::TimedOut => {
+				let faucet = format!("{amount_eth}{token_name} ({chain_name}) to {address}");
+				report_unknown(state, &interaction, visibility, &ratelimit_key, &faucet).await?;
+			}
+			Submission::Done(Err(err)) => {
+				let user_err = UserFacingErr::salt(CorrelationId::of(interaction.id), &err);
+				let msg = format!(
+					"Error transacting {amount_eth}{token_name} ({chain_name}) to {address}: {user_err}"
 				);
-				follow_up(state, &interaction, err_string)
-					.await
-					.wrap_err("Couldn't follow up on a failed transaction with an error message")?;
+				let retry = Retry {
+					discord_id,
+					chain_id,
+					token_name,
+					address,
+				};
+				follow_up_or_post(
+					state,
+					&interaction,
+					visibility,
+					discord_id,
+					msg,
+					[retry.button()],
+				)
+				.await
+				.wrap_err("Couldn't follow up on a failed transaction with an error message")?;
 			}
-			Ok(data) => {
+			Submission::Done(Ok(data)) => {
 				// still registers even if expanded limits
 				state
 					.ratelimits
 					.lock()
 					.await?
-					.register(&ratelimit_key)
+					.register(&ratelimit_key, Some(data.hash))
 					.await
 					.wrap_err("Couldn't register successful bot transaction")?;
 				let explorer_url = self.block_explorer().transaction_explorer_url(data.hash)?;
-				follow_up(state, &interaction, &format!(
+				let explorer = link_button(&explorer_url, "View on explorer");
+				let shown = follow_up_or_post(state, &interaction, visibility, discord_id, &format!(
 					"Successful faucet of {amount_eth}{token_name} ({chain_name}) to {address}\nSee the final broadcasted transaction here: <{explorer_url}>"
-				))
+				), [explorer.clone()])
 				.await?;
+				if shown == Visibility::Ephemeral && replies.public_success {
+					follow_up_or_post(state, &interaction, Visibility::Public, discord_id, format!(
+						"<@{discord_id}> was successfully fauceted {amount_eth}{token_name} ({chain_name})! <{explorer_url}>"
+					), [explorer])
+					.await?;
+				}
 				info!("Finished handling the discord interaction");
 			}
 		}
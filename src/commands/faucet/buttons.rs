@@ -0,0 +1,195 @@
+use alloy::primitives::Address;
+use twilight_model::{
+	application::interaction::Interaction,
+	channel::message::component::{Button, ButtonStyle, Component},
+	http::interaction::{InteractionResponse, InteractionResponseType},
+	id::{
+		Id,
+		marker::{InteractionMarker, UserMarker},
+	},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
+
+use crate::{
+	cancel::{Cancelled, Submitted, Ticket},
+	commands::{
+		button,
+		faucet::unified::Offer,
+		respond_ephemeral,
+		router::{Handle as _, Press},
+	},
+	common::GlobalStateRef,
+	prelude::*,
+};
+
+/// Runs a failed faucet again, subject to the usual ratelimits
+#[derive(Debug, Clone)]
+pub(crate) struct Retry {
+	pub discord_id: Id<UserMarker>,
+	pub chain_id: u64,
+	pub token_name: &'static str,
+	pub address: Address,
+}
+
+impl Retry {
+	pub fn button(&self) -> Button {
+		button(self.custom_id(), "Retry", ButtonStyle::Primary)
+	}
+
+	/// From the Retry button of one of the bot's faucet messages
+	pub fn from_components(components: &[Component]) -> Option<Result<Retry>> {
+		components
+			.iter()
+			.flat_map(|component| match component {
+				Component::ActionRow(row) => row.components.as_slice(),
+				_ => [].as_slice(),
+			})
+			.find_map(|component| match component {
+				Component::Button(Button {
+					custom_id: Some(custom_id),
+					..
+				}) => custom_id.strip_prefix(Self::PREFIX)?.strip_prefix(':'),
+				_ => None,
+			})
+			.map(Retry::parse)
+	}
+
+	/// Run by an admin, see [super::FaucetCommand::handle_on_behalf_of]
+	pub async fn on_behalf(
+		self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> Result<()> {
+		Offer::find(&self.chain_id.to_string(), Some(self.token_name))?
+			.command(self.address.to_string())
+			.handle_on_behalf_of(state, interaction, self.discord_id)
+			.await
+	}
+}
+
+impl Press for Retry {
+	const PREFIX: &'static str = "retry";
+
+	fn args(&self) -> String {
+		format!(
+			"{}:{}:{}:{}",
+			self.discord_id, self.chain_id, self.token_name, self.address
+		)
+	}
+
+	fn parse(args: &str) -> Result<Self> {
+		let mut args = args.split(':');
+		let mut next = || args.next().ok_or_else(|| eyre!("Missing retry argument"));
+		let discord_id = next()?.parse()?;
+		let chain_id = next()?.parse()?;
+		let token = next()?;
+		let address = next()?.parse()?;
+		let offer = Offer::find(&chain_id.to_string(), Some(token))?;
+		Ok(Retry {
+			discord_id,
+			chain_id,
+			token_name: offer.token_name,
+			address,
+		})
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		if interaction.author_id() != Some(self.discord_id) {
+			let msg = format!("Only <@{}> can retry their faucet", self.discord_id);
+			return respond_ephemeral(state, &interaction, msg).await;
+		}
+		Offer::find(&self.chain_id.to_string(), Some(self.token_name))?
+			.command(self.address.to_string())
+			.handle(state, interaction)
+			.await
+	}
+}
+
+/// Stops a faucet before its Salt proposal is submitted, see [crate::cancel::Cancellable]
+#[derive(Debug, Clone)]
+pub(crate) struct Cancel {
+	/// That started the faucet
+	pub interaction: Id<InteractionMarker>,
+}
+
+impl Cancel {
+	pub fn button(&self) -> Button {
+		button(self.custom_id(), "Cancel", ButtonStyle::Danger)
+	}
+}
+
+impl Press for Cancel {
+	const PREFIX: &'static str = "cancel";
+
+	fn args(&self) -> String {
+		self.interaction.to_string()
+	}
+
+	fn parse(args: &str) -> Result<Self> {
+		Ok(Cancel {
+			interaction: args.parse()?,
+		})
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		let user = interaction
+			.author_id()
+			.ok_or_else(|| eyre!("Button pressed without a user"))?;
+		if let Err(err) = state.cancellable.cancel(self.interaction, user) {
+			return respond_ephemeral(state, &interaction, err.to_string()).await;
+		}
+		// replaces the faucet's message, removing the button
+		state
+			.client
+			.interaction(interaction.application_id)
+			.create_response(
+				interaction.id,
+				&interaction.token,
+				&InteractionResponse {
+					kind: InteractionResponseType::UpdateMessage,
+					data: Some(
+						InteractionResponseDataBuilder::new()
+							.content(format!("<@{user}> cancelled this faucet, nothing was sent"))
+							.components([])
+							.build(),
+					),
+				},
+			)
+			.await
+			.wrap_err("Couldn't update a cancelled faucet's message")
+			.map(|_| ())
+	}
+}
+
+/// Right before submitting the Salt proposal, [None] if the faucet was cancelled instead.
+/// Either way the cancel button is removed
+pub(super) async fn submit<'a>(
+	state: GlobalStateRef<'_>,
+	interaction: &Interaction,
+	ticket: Ticket<'a>,
+) -> Option<Submitted<'a>> {
+	match ticket.submit() {
+		Ok(submitted) => {
+			remove_cancel(state, interaction).await;
+			Some(submitted)
+		}
+		// the requester's button press already updated the message
+		Err(Cancelled) => {
+			info!("Faucet cancelled before submitting it to Salt");
+			None
+		}
+	}
+}
+
+/// Once a faucet can't be cancelled any more, best effort
+async fn remove_cancel(state: GlobalStateRef<'_>, interaction: &Interaction) {
+	let res = state
+		.client
+		.interaction(interaction.application_id)
+		.update_response(&interaction.token)
+		.components(Some(&[]))
+		.await;
+	if let Err(err) = res {
+		warn!(?err, "Couldn't remove a faucet's cancel button");
+	}
+}
@@ -5,17 +5,26 @@ use alloy::sol_types::SolCall as _;
 use color_eyre::eyre::Context as _;
 use or_poisoned::OrPoisoned as _;
 use twilight_interactions::command::{CommandModel, CreateCommand};
-use twilight_model::{
-	application::interaction::Interaction,
-	http::interaction::{InteractionResponse, InteractionResponseType},
-};
-use twilight_util::builder::InteractionResponseDataBuilder;
+use twilight_model::application::interaction::Interaction;
 
 use crate::{
-	chains::{BlockchainListing, SomniaShannon},
-	commands::faucet::DiscordInfo,
+	chains::{
+		BlockchainListing, SomniaShannon, SupportedChain,
+		explorer::{BlockchainExplorer as _, ExplorableBlockchain as _},
+	},
+	commands::{
+		Visibility, defer_as, edit_response_with,
+		faucet::{
+			DiscordInfo, Submission, audit_refusal,
+			buttons::{Cancel, Retry, submit},
+			record_event, report_unknown, time_limited,
+		},
+		follow_up_or_post, link_button, respond_as,
+	},
 	common::GlobalStateRef,
+	env::Env,
 	ratelimits,
+	user_errors::{CorrelationId, UserFacingErr},
 };
 
 /// Faucet 0.05 PING on Somnia Shannon (an ERC20 token)
@@ -47,14 +56,31 @@ impl SomniaShannonPing {
 		}
 	}
 
-	fn amount() -> U256 {
+	pub(super) fn new(address: String) -> SomniaShannonPing {
+		SomniaShannonPing { address }
+	}
+
+	pub(super) fn amount() -> U256 {
 		parse_ether("0.05").unwrap()
 	}
 
-	fn erc20_token_name() -> &'static str {
+	pub(super) fn erc20_token_name() -> &'static str {
 		"PING"
 	}
 
+	/// The faucet vault's balance of the token
+	pub(super) async fn vault_balance(env: &Env) -> color_eyre::Result<U256> {
+		let provider = alloy::providers::ProviderBuilder::new()
+			.connect(env.somnia_shannon_rpc_endpoint.as_str())
+			.await
+			.wrap_err("Couldn't connect to the Somnia Shannon RPC")?;
+		ERC20::new(Self::SMART_CONTRACT_ADDR, provider)
+			.balanceOf(env.faucet_testnet_salt_account_address)
+			.call()
+			.await
+			.wrap_err("Couldn't get the PING balance")
+	}
+
 	const SMART_CONTRACT_ADDR: Address = address!("0x33E7fAB0a8a5da1A923180989bD617c9c2D1C493");
 }
 
@@ -94,37 +120,23 @@ impl SomniaShannonPing {
 		let rpc_url = &state.env.somnia_shannon_rpc_endpoint;
 		let DiscordInfo {
 			discord_id,
+			requester,
 			has_expanded_limits,
+			replies,
+			..
 		} = discord_info;
 
-		let respond = async |msg: &str| {
-			state
-				.client
-				.interaction(interaction.application_id)
-				.create_response(
-					interaction.id,
-					&interaction.token,
-					&InteractionResponse {
-						kind: InteractionResponseType::ChannelMessageWithSource,
-						data: Some(InteractionResponseDataBuilder::new().content(msg).build()),
-					},
-				)
-				.await
-				.wrap_err("Couldn't initially respond to a discord interaction")
-		};
-		let follow_up = async |msg: &str| {
-			state
-				.client
-				.interaction(interaction.application_id)
-				.create_followup(&interaction.token)
-				.content(msg.as_ref())
-				.await
-				.wrap_err("Couldn't followup a discord interaction")
+		let visibility = replies.visibility;
+		let respond = async |msg: &str| respond_as(state, &interaction, visibility, msg).await;
+		let follow_up = async |msg: String| {
+			follow_up_or_post(state, &interaction, visibility, discord_id, msg, []).await
 		};
 
+		let request = format!("{token_name} ({chain_name})");
 		let address = match address.parse() {
 			Ok(address) => address,
 			Err(err) => {
+				audit_refusal(state, &interaction, discord_id, request, "invalid address");
 				respond(&format!(
 					"Invalid Etherium wallet address {:?}: {}",
 					self.address, err
@@ -134,19 +146,44 @@ impl SomniaShannonPing {
 			}
 		};
 
+		// the RPC calls below can take longer than discord waits for a response
+		defer_as(state, &interaction, visibility).await?;
+		let edit = async |msg: &str| edit_response_with(state, &interaction, msg, []).await;
+
+		// check the recipient has real activity elsewhere
+		if let Some(proof) = &state.env.proof_of_activity
+			&& let Err(err) = proof.check(address).await
+		{
+			warn!(%address, ?err, "Recipient failed the proof of activity check");
+			let request = format!("{request} to `{address}`");
+			audit_refusal(
+				state,
+				&interaction,
+				discord_id,
+				request,
+				"no proof of activity",
+			);
+			edit(&err.to_string()).await?;
+			return Ok(());
+		}
+
 		// check ratelimiting if not expanded limits
 		let ratelimit_key = ratelimits::Key {
 			address,
 			discord_id,
 			chain_id,
 			chain_name,
+			token_name,
 		};
 		if !has_expanded_limits {
 			let ratelimit = state.ratelimits.lock().await?.check(&ratelimit_key);
 			if let Err(msg) = ratelimit {
+				state.metrics.ratelimit_refusal(chain_name, token_name);
+				let request = format!("{request} to `{address}`");
+				audit_refusal(state, &interaction, discord_id, request, "ratelimited");
 				let msg =
 					format!("Couldn't faucet you any tokens because you are ratelimited!\n{msg}");
-				respond(&msg).await?;
+				edit(&msg).await?;
 				return Ok(());
 			}
 		} else {
@@ -160,21 +197,34 @@ impl SomniaShannonPing {
 		.abi_encode();
 
 		// initial response
-		respond(&format!(
-			"Starting faucet of {amount_eth}{token_name}, an ERC20 token ({chain_name}), to {address} ..."
-		))
+		let ticket = state.cancellable.register(interaction.id, requester);
+		edit_response_with(
+			state,
+			&interaction,
+			format!(
+				"Starting faucet of {amount_eth}{token_name}, an ERC20 token ({chain_name}), to {address} ..."
+			),
+			[Cancel {
+				interaction: interaction.id,
+			}
+			.button()],
+		)
 		.await?;
+		let started = std::time::Instant::now();
 
 		// do transaction
 		let (send_logs, mut live_logs) = tokio::sync::mpsc::channel(10);
 		let mut live_logging = salt_sdk::LiveLogging::from_sender(send_logs);
 		let salt_config = salt_sdk::SaltConfig {
-			private_key: state.env.private_key.clone(),
+			private_key: state.private_key.to_hex(),
 			orchestration_network_rpc_node: state.env.sepolia_arbitrum_rpc_endpoint.clone(),
 			broadcasting_network_rpc_node: rpc_url.clone(),
 			broadcasting_network_id: chain_id,
 		};
 		let salt = salt_sdk::Salt::new(salt_config)?;
+		let Some(_submitted) = submit(state, &interaction, ticket).await else {
+			return Ok(());
+		};
 		let transaction = salt.transaction(salt_sdk::TransactionInfo {
 			amount: U256::from(0),
 			vault_address: state.env.faucet_testnet_salt_account_address,
@@ -186,60 +236,87 @@ impl SomniaShannonPing {
 			auto_broadcast: true,
 		});
 		let logging = async move {
+			let mut last = started;
 			while let Some(log) = live_logs.recv().await {
 				info!(%log, "Sending live log");
-				follow_up(&log.to_string())
+				state.metrics.salt_stage(&log, &mut last);
+				follow_up(log.to_string())
 					.await
 					.wrap_err("Live logging failed to send")?;
 			}
 			Result::<(), color_eyre::Report>::Ok(())
 		};
 
-		let (res, logging_err) = tokio::join!(transaction, logging);
+		let (res, logging_err) = time_limited(async { tokio::join!(transaction, logging) }).await;
 
 		if let Err(err) = logging_err {
 			error!("Failed to send live logs:\n{}", err);
 		}
 
-		if let Err(err) = res {
-			error!("Failed to do salt transaction:\n{}", err);
-			let mut err_string = err.to_string();
-
-			if let salt_sdk::Error::SubprocessExitedBadlyWithOutput(output) = err {
-				err_string = output.stderr;
-			}
+		record_event(
+			state,
+			interaction.guild_id,
+			CorrelationId::of(interaction.id),
+			&ratelimit_key,
+			amount,
+			&res,
+			started,
+			&self.plain().block_explorer().adapt::<SupportedChain>(),
+		)
+		.await;
 
-			if err_string.len() > 1900 {
-				// only keeps first 1900 bytes, avoiding a panic if using String.split_off
-				// https://doc.rust-lang.org/stable/std/string/struct.String.html#method.split_off
-				let truncated = err_string
-					.into_bytes()
-					.into_iter()
-					.take(1900)
-					.collect::<Vec<u8>>();
-				let truncated = String::from_utf8_lossy(&truncated);
-				err_string = format!("{truncated}...<truncated>");
+		match res {
+			Submission::TimedOut => {
+				let faucet = format!("{amount_eth}{token_name} ({chain_name}) to {address}");
+				report_unknown(state, &interaction, visibility, &ratelimit_key, &faucet).await?;
 			}
-			err_string = format!(
-				"Error transacting {amount_eth}{token_name} ({chain_name}) to {address}:\n{err_string}"
-			);
-			follow_up(&err_string)
+			Submission::Done(Err(err)) => {
+				let user_err = UserFacingErr::salt(CorrelationId::of(interaction.id), &err);
+				let retry = Retry {
+					discord_id,
+					chain_id,
+					token_name,
+					address,
+				};
+				follow_up_or_post(
+					state,
+					&interaction,
+					visibility,
+					discord_id,
+					format!(
+						"Error transacting {amount_eth}{token_name} ({chain_name}) to {address}: {user_err}"
+					),
+					[retry.button()],
+				)
 				.await
 				.wrap_err("Couldn't follow up on a failed transaction with an error message")?;
-		} else {
-			// still registers even if expanded limits
-			state
-				.ratelimits
-				.lock()
-				.await?
-				.register(&ratelimit_key)
-				.await
-				.wrap_err("Couldn't register successful bot transaction")?;
-			follow_up(&format!(
-				"Successful faucet of {amount_eth}{token_name} ({chain_name}) to {address}"
-			))
-			.await?;
-			info!("Finished handling the discord interaction");
+			}
+			Submission::Done(Ok(data)) => {
+				// still registers even if expanded limits
+				state
+					.ratelimits
+					.lock()
+					.await?
+					.register(&ratelimit_key, Some(data.hash))
+					.await
+					.wrap_err("Couldn't register successful bot transaction")?;
+				let explorer_url = self
+					.plain()
+					.block_explorer()
+					.transaction_explorer_url(data.hash)?;
+				let explorer = link_button(&explorer_url, "View on explorer");
+				let shown = follow_up_or_post(state, &interaction, visibility, discord_id, format!(
+					"Successful faucet of {amount_eth}{token_name} ({chain_name}) to {address}\nSee the final broadcasted transaction here: <{explorer_url}>"
+				), [explorer.clone()])
+				.await?;
+				if shown == Visibility::Ephemeral && replies.public_success {
+					follow_up_or_post(state, &interaction, Visibility::Public, discord_id, format!(
+						"<@{discord_id}> was successfully fauceted {amount_eth}{token_name} ({chain_name})! <{explorer_url}>"
+					), [explorer])
+					.await?;
+				}
+				info!("Finished handling the discord interaction");
+			}
 		}
 
 		Ok(())
@@ -0,0 +1,96 @@
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::application::interaction::Interaction;
+
+use crate::{
+	chains::{
+		BlockchainListing as _, SupportedChain,
+		explorer::{BlockchainExplorer as _, ExplorableBlockchain as _},
+	},
+	commands::{faucet::DiscordInfo, respond_as},
+	common::GlobalStateRef,
+	prelude::*,
+};
+
+use super::erc20::SomniaShannonPing;
+
+/// Check how many faucets you have left and your recent faucet transactions
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "status")]
+pub struct Status;
+
+impl Status {
+	const RECENT_CLAIMS: usize = 5;
+
+	pub async fn handle(
+		&self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+		discord_info: DiscordInfo,
+	) -> color_eyre::Result<()> {
+		let discord_id = discord_info.discord_id;
+		let msg = {
+			let ratelimits = state.ratelimits.lock().await?;
+
+			let mut msg = String::from("**Your faucet allowance**\n");
+			if discord_info.has_expanded_limits {
+				msg.push_str("You have expanded limits, so you aren't ratelimited\n");
+			}
+			let grants = ratelimits.remaining_grants(discord_id);
+			if grants > 0 {
+				msg.push_str(&format!(
+					"You also have {grants} extra faucet(s) granted by an admin\n"
+				));
+			}
+			for chain in SupportedChain::all() {
+				let allowance = ratelimits.allowance(chain.chain_id(), discord_id);
+				let mut tokens = vec![chain.native_token_name()];
+				if matches!(chain, SupportedChain::SomniaShannon(_)) {
+					tokens.push(SomniaShannonPing::erc20_token_name());
+				}
+				msg.push_str(&format!(
+					"- {} ({}): {} claim(s) left",
+					chain.chain_name(),
+					tokens.join(", "),
+					allowance.remaining,
+				));
+				if let Some(next_available) = allowance.next_available {
+					msg.push_str(&format!(
+						", next available <t:{}:R>",
+						next_available.unix_timestamp()
+					));
+				}
+				msg.push('\n');
+			}
+
+			let claims = ratelimits.recent_claims(discord_id, Self::RECENT_CLAIMS);
+			if claims.is_empty() {
+				msg.push_str("\nYou haven't received any faucets yet\n");
+			} else {
+				msg.push_str("\n**Your recent faucets**\n");
+			}
+			for (chain_id, claim) in claims {
+				let chain = SupportedChain::all()
+					.into_iter()
+					.find(|chain| chain.chain_id() == chain_id);
+				let link = match (chain, claim.tx_hash) {
+					(_, None) => "unknown outcome, check the explorer".to_owned(),
+					(Some(chain), Some(tx_hash)) => chain
+						.block_explorer()
+						.transaction_explorer_url(tx_hash)
+						.map(|url| format!("<{url}>"))
+						.unwrap_or_else(|_| tx_hash.to_string()),
+					(None, Some(tx_hash)) => tx_hash.to_string(),
+				};
+				msg.push_str(&format!(
+					"- <t:{}:f> {} to {}: {link}\n",
+					claim.at.unix_timestamp(),
+					claim.token_name,
+					claim.address,
+				));
+			}
+			msg
+		};
+
+		respond_as(state, &interaction, discord_info.replies.visibility, msg).await
+	}
+}
@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+
+use alloy::primitives::{
+	U256,
+	utils::{ParseUnits, Unit},
+};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+	application::{
+		command::{CommandOptionChoice, CommandOptionChoiceValue},
+		interaction::Interaction,
+	},
+	id::{Id, marker::UserMarker},
+};
+
+use crate::{
+	chains::{self, BlockchainListing as _, NativeFaucet as _, SupportedChain},
+	commands::{
+		faucet::{FaucetCommand, erc20::SomniaShannonPing},
+		respond_ephemeral,
+		router::{Autocomplete, Handle, Partial},
+	},
+	common::GlobalStateRef,
+	prelude::*,
+	ratelimits::RateLimits,
+};
+
+/// Faucet some crypto from a testing Salt account, pick a chain and token from the suggestions
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(name = "faucet")]
+pub(crate) struct UnifiedFaucetCommand {
+	/// The chain to faucet on
+	#[command(autocomplete = true)]
+	chain: String,
+
+	/// Your personal wallet address
+	#[command(autocomplete = true)]
+	address: String,
+
+	/// Defaults to the chain's native token
+	#[command(autocomplete = true)]
+	token: Option<String>,
+}
+
+/// One token on one chain, the same as one of [FaucetCommand]'s subcommands
+#[derive(Debug, Clone)]
+pub(super) struct Offer {
+	chain: SupportedChain,
+	pub(super) token_name: &'static str,
+	amount: U256,
+	erc20: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(super) enum UnknownOfferErr {
+	#[error("The faucet doesn't support the chain {0:?}, please pick one of the suggestions")]
+	Chain(String),
+	#[error(
+		"The faucet doesn't give out {token:?} on {chain_name}, please pick one of the suggestions"
+	)]
+	Token {
+		token: String,
+		chain_name: &'static str,
+	},
+}
+
+impl Offer {
+	/// Each chain's native token comes first
+	fn all() -> Vec<Offer> {
+		let mut offers = Vec::new();
+		for chain in SupportedChain::all() {
+			let erc20 = matches!(chain, SupportedChain::SomniaShannon(_)).then(|| Offer {
+				chain: chain.clone(),
+				token_name: SomniaShannonPing::erc20_token_name(),
+				amount: SomniaShannonPing::amount(),
+				erc20: true,
+			});
+			offers.push(Offer {
+				token_name: chain.native_token_name(),
+				amount: chain.faucet_amount(),
+				chain,
+				erc20: false,
+			});
+			offers.extend(erc20);
+		}
+		offers
+	}
+
+	/// By chain ID, which the suggestions use, or by name if typed out
+	fn is_on(&self, chain: &str) -> bool {
+		let chain = chain.trim();
+		chain == self.chain.chain_id().to_string()
+			|| chain.eq_ignore_ascii_case(self.chain.chain_name())
+	}
+
+	pub(super) fn find(chain: &str, token: Option<&str>) -> Result<Offer, UnknownOfferErr> {
+		let mut offers = Offer::all()
+			.into_iter()
+			.filter(|offer| offer.is_on(chain))
+			.peekable();
+		let Some(chain_name) = offers.peek().map(|offer| offer.chain.chain_name()) else {
+			return Err(UnknownOfferErr::Chain(chain.to_owned()));
+		};
+		match token.map(str::trim) {
+			None => offers.find(|offer| !offer.erc20),
+			Some(token) => offers.find(|offer| offer.token_name.eq_ignore_ascii_case(token)),
+		}
+		.ok_or_else(|| UnknownOfferErr::Token {
+			token: token.unwrap_or_default().to_owned(),
+			chain_name,
+		})
+	}
+
+	pub(super) fn command(self, address: String) -> FaucetCommand {
+		if self.erc20 {
+			return FaucetCommand::PingSomniaShannon(SomniaShannonPing::new(address));
+		}
+		match self.chain {
+			SupportedChain::SomniaShannon(_) => {
+				FaucetCommand::SomniaShannon(chains::SomniaShannon { address })
+			}
+			SupportedChain::SepoliaEtherium(_) => {
+				FaucetCommand::SepoliaEtherium(chains::SepoliaEthereum { address })
+			}
+			SupportedChain::SepoliaArbitrum(_) => {
+				FaucetCommand::SepoliaArbitrum(chains::SepoliaArbitrum { address })
+			}
+			SupportedChain::PolygonAmoy(_) => {
+				FaucetCommand::PolygonAmoy(chains::PolygonAmoy { address })
+			}
+		}
+	}
+}
+
+impl Handle for UnifiedFaucetCommand {
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		match Offer::find(&self.chain, self.token.as_deref()) {
+			Ok(offer) => offer.command(self.address).handle(state, interaction).await,
+			Err(err) => respond_ephemeral(state, &interaction, err.to_string()).await,
+		}
+	}
+}
+
+impl Autocomplete for UnifiedFaucetCommand {
+	async fn autocomplete(
+		state: GlobalStateRef<'_>,
+		interaction: &Interaction,
+		options: Partial<'_>,
+	) -> Result<Vec<CommandOptionChoice>> {
+		let discord_id = interaction
+			.author_id()
+			.ok_or_else(|| eyre!("Autocomplete invoked without a user"))?;
+		let roles = interaction
+			.member
+			.as_ref()
+			.map(|member| member.roles.as_slice())
+			.unwrap_or_default();
+		let unlimited = state
+			.env
+			.guild(interaction.guild_id)
+			.is_some_and(|guild| guild.has_expanded_limits(roles));
+		let input = options.input.trim().to_lowercase();
+		let ratelimits = state.ratelimits.lock().await?;
+		let allowance = |chain: &SupportedChain| {
+			allowance_label(&ratelimits, chain.chain_id(), discord_id, unlimited)
+		};
+
+		let choices = match options.focused {
+			"chain" => SupportedChain::all()
+				.into_iter()
+				.filter(|chain| chain.chain_name().to_lowercase().contains(&input))
+				.map(|chain| {
+					let tokens = Offer::all()
+						.into_iter()
+						.filter(|offer| offer.chain.chain_id() == chain.chain_id())
+						.map(|offer| offer.token_name)
+						.collect::<Vec<_>>()
+						.join(", ");
+					choice(
+						format!("{} ({tokens}), {}", chain.chain_name(), allowance(&chain)),
+						chain.chain_id().to_string(),
+					)
+				})
+				.collect(),
+			"token" => {
+				let chain = options.get("chain").unwrap_or_default();
+				Offer::all()
+					.into_iter()
+					.filter(|offer| chain.is_empty() || offer.is_on(chain))
+					.filter(|offer| offer.token_name.to_lowercase().contains(&input))
+					.map(|offer| {
+						let amount = ParseUnits::from(offer.amount).format_units(Unit::ETHER);
+						choice(
+							format!(
+								"{amount} {} on {}, {}",
+								offer.token_name,
+								offer.chain.chain_name(),
+								allowance(&offer.chain)
+							),
+							offer.token_name.to_owned(),
+						)
+					})
+					.collect()
+			}
+			"address" => {
+				let mut seen = HashSet::new();
+				ratelimits
+					.recent_claims(discord_id, usize::MAX)
+					.into_iter()
+					.filter(|(_, claim)| seen.insert(claim.address))
+					.filter(|(_, claim)| claim.address.to_string().to_lowercase().contains(&input))
+					.map(|(chain_id, claim)| {
+						let chain_name = SupportedChain::all()
+							.into_iter()
+							.find(|chain| chain.chain_id() == chain_id)
+							.map(|chain| chain.chain_name())
+							.unwrap_or("an unsupported chain");
+						choice(
+							format!("{} (last used on {chain_name})", claim.address),
+							claim.address.to_string(),
+						)
+					})
+					.collect()
+			}
+			_ => Vec::new(),
+		};
+		Ok(choices)
+	}
+}
+
+fn allowance_label(
+	ratelimits: &RateLimits,
+	chain_id: u64,
+	discord_id: Id<UserMarker>,
+	unlimited: bool,
+) -> String {
+	if unlimited {
+		return "no limit".to_owned();
+	}
+	let allowance = ratelimits.allowance(chain_id, discord_id);
+	match allowance.next_available {
+		None => format!("{} claim(s) left today", allowance.remaining),
+		Some(_) => "no claims left for now".to_owned(),
+	}
+}
+
+fn choice(name: String, value: String) -> CommandOptionChoice {
+	CommandOptionChoice {
+		name,
+		name_localizations: None,
+		value: CommandOptionChoiceValue::String(value),
+	}
+}
+
+#[test]
+fn offers_resolve() {
+	let native = Offer::find("50312", None).expect("Somnia Shannon to be supported");
+	assert_eq!(native.token_name, "STT");
+	assert!(!native.erc20);
+
+	let erc20 = Offer::find("somnia shannon", Some("ping")).expect("PING to be on Somnia Shannon");
+	assert!(erc20.erc20);
+	assert!(matches!(
+		erc20.command(String::new()),
+		FaucetCommand::PingSomniaShannon(_)
+	));
+
+	assert!(matches!(
+		Offer::find("80002", Some("PING")),
+		Err(UnknownOfferErr::Token { .. })
+	));
+	assert!(matches!(
+		Offer::find("nowhere", None),
+		Err(UnknownOfferErr::Chain(_))
+	));
+}
@@ -0,0 +1,283 @@
+use alloy::primitives::{
+	TxHash,
+	utils::{ParseUnits, Unit},
+};
+use time::OffsetDateTime;
+use twilight_model::{
+	application::{
+		command::CommandType,
+		interaction::{Interaction, application_command::CommandData},
+	},
+	channel::{
+		Message,
+		message::{Component, component::Button},
+	},
+	id::{Id, marker::UserMarker},
+};
+
+use crate::{
+	chains::{BlockchainListing as _, SupportedChain},
+	commands::{
+		admin::ratelimits::{block, truncate},
+		faucet::Retry,
+		respond, respond_ephemeral,
+		router::{ContextMenu, target_message, target_user},
+	},
+	common::GlobalStateRef,
+	prelude::*,
+	ratelimits::Target,
+	stats::{FaucetEvent, FaucetEvents, Outcome},
+	user_errors::CorrelationId,
+};
+
+/// The same as `/admin ratelimits show` plus the user's recent claims
+pub(crate) struct FaucetHistory(Id<UserMarker>);
+
+impl ContextMenu for FaucetHistory {
+	const NAME: &'static str = "Faucet history";
+	const KIND: CommandType = CommandType::User;
+
+	fn parse(data: CommandData) -> Result<Self> {
+		Ok(FaucetHistory(target_user(data)?.id))
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		let ratelimits = state.ratelimits.lock().await?;
+		let mut msg = ratelimits.describe(Target::User(self.0));
+		let claims = ratelimits.recent_claims(self.0, 10);
+		if claims.is_empty() {
+			msg.push_str("No successful faucets on record\n");
+		} else {
+			msg.push_str("Recent faucets:\n");
+		}
+		for (chain_id, claim) in claims {
+			let chain_name = SupportedChain::all()
+				.into_iter()
+				.find(|chain| chain.chain_id() == chain_id)
+				.map(|chain| chain.chain_name())
+				.unwrap_or("an unsupported chain");
+			let tx_hash = match claim.tx_hash {
+				Some(tx_hash) => format!("`{tx_hash}`"),
+				None => "unknown outcome".to_owned(),
+			};
+			msg.push_str(&format!(
+				"- <t:{}:R> {} on {chain_name} to {}: {tx_hash}\n",
+				claim.at.unix_timestamp(),
+				claim.token_name,
+				claim.address,
+			));
+		}
+		drop(ratelimits);
+		respond_ephemeral(state, &interaction, truncate(msg)).await
+	}
+}
+
+/// The same as `/admin ratelimits reset` on every chain, including the user's addresses
+pub(crate) struct ResetRatelimits(Id<UserMarker>);
+
+impl ContextMenu for ResetRatelimits {
+	const NAME: &'static str = "Reset rate limits";
+	const KIND: CommandType = CommandType::User;
+
+	fn parse(data: CommandData) -> Result<Self> {
+		Ok(ResetRatelimits(target_user(data)?.id))
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		let target = Target::User(self.0);
+		let removed = state.ratelimits.lock().await?.reset(target, None).await?;
+		let msg = format!(
+			"Reset the ratelimits of {target} and the addresses they claimed to on every chain, removing {removed} record(s)"
+		);
+		respond(state, &interaction, msg).await
+	}
+}
+
+/// The same as `/admin ratelimits block`
+pub(crate) struct BlockFromFaucet(Id<UserMarker>);
+
+impl ContextMenu for BlockFromFaucet {
+	const NAME: &'static str = "Block from faucet";
+	const KIND: CommandType = CommandType::User;
+
+	fn parse(data: CommandData) -> Result<Self> {
+		Ok(BlockFromFaucet(target_user(data)?.id))
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		let msg = block(state, self.0).await?;
+		respond(state, &interaction, msg).await
+	}
+}
+
+/// Presses a failed faucet's Retry button on the requester's behalf
+pub(crate) struct RetryThisFaucet(Message);
+
+impl ContextMenu for RetryThisFaucet {
+	const NAME: &'static str = "Retry this faucet";
+	const KIND: CommandType = CommandType::Message;
+
+	fn parse(data: CommandData) -> Result<Self> {
+		Ok(RetryThisFaucet(target_message(data)?))
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		if !is_own_message(state, &self.0).await? {
+			return respond_ephemeral(state, &interaction, NOT_FAUCET_MESSAGE).await;
+		}
+		match Retry::from_components(&self.0.components) {
+			Some(retry) => retry?.on_behalf(state, interaction).await,
+			None => {
+				let msg = "Only failed faucets with a Retry button can be retried";
+				respond_ephemeral(state, &interaction, msg).await
+			}
+		}
+	}
+}
+
+/// Finds the faucet a message is about in the [FaucetEvents]
+pub(crate) struct ShowTxDetails(Message);
+
+impl ContextMenu for ShowTxDetails {
+	const NAME: &'static str = "Show tx details";
+	const KIND: CommandType = CommandType::Message;
+
+	fn parse(data: CommandData) -> Result<Self> {
+		Ok(ShowTxDetails(target_message(data)?))
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		if !is_own_message(state, &self.0).await? {
+			return respond_ephemeral(state, &interaction, NOT_FAUCET_MESSAGE).await;
+		}
+		let Some(faucet) = Faucet::of(&self.0) else {
+			let msg = "Couldn't find a transaction or error reference in this message";
+			return respond_ephemeral(state, &interaction, msg).await;
+		};
+		let posted =
+			OffsetDateTime::from_unix_timestamp_nanos(self.0.timestamp.as_micros() as i128 * 1_000)
+				.wrap_err("Message timestamp out of range")?;
+		let event = FaucetEvents::read_since(posted - EVENT_MARGIN)
+			.await?
+			.into_iter()
+			.rfind(|event| faucet.is(event));
+		let msg = match event {
+			Some(event) => describe(&event),
+			None => format!("No faucet on record for {faucet}"),
+		};
+		let msg = format!("{msg}\nUse `/admin trace {faucet}` for everything that was logged");
+		respond_ephemeral(state, &interaction, truncate(msg)).await
+	}
+}
+
+/// Faucets are recorded after their first message is posted, this allows for clock skew
+const EVENT_MARGIN: time::Duration = time::Duration::hours(1);
+
+const NOT_FAUCET_MESSAGE: &str = "This only works on the faucet's own messages";
+
+/// By author, as messages posted in the channel rather than as an interaction reply
+/// have no application id
+async fn is_own_message(state: GlobalStateRef<'_>, message: &Message) -> Result<bool> {
+	let bot = state
+		.client
+		.current_user()
+		.await
+		.wrap_err("Couldn't get the bot's user")?
+		.model()
+		.await
+		.wrap_err("Couldn't parse the bot's user")?;
+	Ok(message.author.id == bot.id)
+}
+
+/// What identifies a faucet in one of its messages
+enum Faucet {
+	/// From the explorer button of a success
+	Success(TxHash),
+	/// From the error reference of a failure, or of a faucet with an unknown outcome
+	Failure(CorrelationId),
+}
+
+impl std::fmt::Display for Faucet {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Faucet::Success(tx_hash) => write!(f, "{tx_hash}"),
+			Faucet::Failure(correlation_id) => write!(f, "{correlation_id}"),
+		}
+	}
+}
+
+impl Faucet {
+	fn of(message: &Message) -> Option<Faucet> {
+		let tx_hash = message
+			.components
+			.iter()
+			.flat_map(|component| match component {
+				Component::ActionRow(row) => row.components.as_slice(),
+				_ => [].as_slice(),
+			})
+			.find_map(|component| match component {
+				Component::Button(Button { url: Some(url), .. }) => {
+					url.rsplit('/').next()?.parse().ok()
+				}
+				_ => None,
+			});
+		if let Some(tx_hash) = tx_hash {
+			return Some(Faucet::Success(tx_hash));
+		}
+		reference(&message.content).map(Faucet::Failure)
+	}
+
+	fn is(&self, event: &FaucetEvent) -> bool {
+		match (self, &event.outcome) {
+			(Faucet::Success(hash), Outcome::Success { tx_hash }) => hash == tx_hash,
+			(
+				Faucet::Failure(id),
+				Outcome::Failure {
+					correlation_id: Some(correlation_id),
+					..
+				},
+			) => id == correlation_id,
+			(Faucet::Failure(id), Outcome::Unknown { correlation_id }) => id == correlation_id,
+			_ => false,
+		}
+	}
+}
+
+/// As formatted by [crate::user_errors::UserFacingErr]
+fn reference(content: &str) -> Option<CorrelationId> {
+	let (_, reference) = content.split_once("(reference `")?;
+	let (reference, _) = reference.split_once('`')?;
+	reference.parse().ok()
+}
+
+fn describe(event: &FaucetEvent) -> String {
+	let outcome = match &event.outcome {
+		Outcome::Success { tx_hash } => format!("Succeeded with transaction `{tx_hash}`"),
+		Outcome::Failure { reason, .. } => format!("Failed: {reason}"),
+		Outcome::Unknown { .. } => {
+			"Unknown, Salt was given up on and the transaction may still have been sent".to_owned()
+		}
+	};
+	format!(
+		"Faucet of {} {} on {} (chain ID {}) to {} for <@{}>\nStarted <t:{}:f>, took {:.1}s\n{outcome}",
+		ParseUnits::from(event.amount).format_units(Unit::ETHER),
+		event.token_name,
+		event.chain_name,
+		event.chain_id,
+		event.address,
+		event.discord_id,
+		event.at.unix_timestamp(),
+		event.latency.as_secs_f64(),
+	)
+}
+
+#[test]
+fn reference_of_failure() {
+	let id = CorrelationId::of(Id::new(1234567890));
+	let err = crate::user_errors::UserFacingErr {
+		message: "An internal error occurred",
+		id,
+	};
+	assert_eq!(reference(&err.to_string()), Some(id));
+	assert_eq!(reference("Successful faucet"), None);
+}
@@ -0,0 +1,656 @@
+use std::{future::Future, pin::Pin};
+
+use color_eyre::Section as _;
+use tracing::Instrument as _;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+	application::{
+		command::{Command, CommandOptionChoice, CommandType},
+		interaction::{
+			Interaction,
+			application_command::{CommandData, CommandOptionValue},
+		},
+	},
+	channel::Message,
+	http::interaction::{InteractionResponse, InteractionResponseType},
+	id::{Id, marker::UserMarker},
+	user::User,
+};
+use twilight_util::builder::{InteractionResponseDataBuilder, command::CommandBuilder};
+
+use crate::{
+	commands::{Visibility, follow_up_as, respond_as},
+	common::{GlobalState, GlobalStateRef},
+	per_user_spam_filter::Guard,
+	prelude::*,
+	user_errors::{CorrelationId, UserFacingErr},
+};
+
+/// Where a command is registered with discord
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scope {
+	/// Every guild and DMs
+	Global,
+	/// Only the guilds from [crate::env::Env::admin_guilds]
+	AdminGuilds,
+}
+
+/// Checks run in order before a command's handler, any of which can refuse the interaction
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Middleware {
+	/// Refuses DMs
+	GuildOnly(&'static str),
+	/// [crate::guilds::GuildConfig::check_faucet_channel]
+	FaucetChannel,
+	/// [crate::permissions::AdminPermissions] of the invoked subcommand,
+	/// auditing every invocation
+	Admin,
+	/// The same as [Middleware::Admin], with the permissions of this `/admin` subcommand
+	AdminAs(&'static str),
+	/// One interaction per user at a time, except for these subcommands
+	SpamFilter { except: &'static [&'static str] },
+}
+
+/// Implemented by every top level command, registered with [Route::new]
+pub(crate) trait Handle: CommandModel + CreateCommand + Send {
+	/// Errors are logged and shown to the user as a [UserFacingErr]
+	fn handle(
+		self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Suggests values for options marked `#[command(autocomplete = true)]` while the user types.
+/// Middleware isn't run, suggestions shouldn't reveal anything the command wouldn't
+pub(crate) trait Autocomplete: Handle {
+	fn autocomplete(
+		state: GlobalStateRef<'_>,
+		interaction: &Interaction,
+		options: Partial<'_>,
+	) -> impl Future<Output = Result<Vec<CommandOptionChoice>>> + Send;
+}
+
+/// The top level options of an autocomplete interaction, as typed so far
+pub(crate) struct Partial<'a> {
+	/// The name of the option being typed
+	pub focused: &'a str,
+	pub input: &'a str,
+	data: &'a CommandData,
+}
+
+impl<'a> Partial<'a> {
+	fn new(data: &'a CommandData) -> Option<Partial<'a>> {
+		data.options.iter().find_map(|option| match &option.value {
+			CommandOptionValue::Focused(input, _) => Some(Partial {
+				focused: &option.name,
+				input,
+				data,
+			}),
+			_ => None,
+		})
+	}
+
+	/// Another string option's value, if it's been filled in yet
+	pub fn get(&self, name: &str) -> Option<&'a str> {
+		self.data
+			.options
+			.iter()
+			.find(|option| option.name == name)
+			.and_then(|option| match &option.value {
+				CommandOptionValue::String(value) | CommandOptionValue::Focused(value, _) => {
+					Some(value.as_str())
+				}
+				_ => None,
+			})
+	}
+}
+
+type BoxFuture<'a, T = ()> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+type Handler = for<'a> fn(GlobalStateRef<'a>, Interaction, CommandData) -> BoxFuture<'a>;
+type AutocompleteHandler = for<'a> fn(
+	GlobalStateRef<'a>,
+	&'a Interaction,
+	Partial<'a>,
+) -> BoxFuture<'a, Vec<CommandOptionChoice>>;
+
+pub(crate) struct Route {
+	command: Command,
+	scope: Scope,
+	middleware: &'static [Middleware],
+	handler: Handler,
+	autocomplete: Option<AutocompleteHandler>,
+}
+
+impl Route {
+	pub fn new<C: Handle>(scope: Scope, middleware: &'static [Middleware]) -> Route {
+		Route {
+			command: C::create_command().into(),
+			scope,
+			middleware,
+			handler: parse_and_handle::<C>,
+			autocomplete: None,
+		}
+	}
+
+	pub fn autocompleted<C: Autocomplete>(
+		scope: Scope,
+		middleware: &'static [Middleware],
+	) -> Route {
+		Route {
+			autocomplete: Some(suggest::<C>),
+			..Route::new::<C>(scope, middleware)
+		}
+	}
+}
+
+impl Route {
+	/// Shown to everyone like `/admin`, the middleware decides who can use it
+	pub fn context_menu<M: ContextMenu>(scope: Scope, middleware: &'static [Middleware]) -> Route {
+		Route {
+			command: CommandBuilder::new(M::NAME, "", M::KIND).build(),
+			scope,
+			middleware,
+			handler: parse_menu::<M>,
+			autocomplete: None,
+		}
+	}
+}
+
+fn parse_menu<M: ContextMenu>(
+	state: GlobalStateRef<'_>,
+	interaction: Interaction,
+	data: CommandData,
+) -> BoxFuture<'_> {
+	Box::pin(async move {
+		let menu = M::parse(data).wrap_err("Couldn't parse context menu data")?;
+		menu.handle(state, interaction).await
+	})
+}
+
+fn parse_and_handle<C: Handle>(
+	state: GlobalStateRef<'_>,
+	interaction: Interaction,
+	data: CommandData,
+) -> BoxFuture<'_> {
+	Box::pin(async move {
+		let command = C::from_interaction(data.into()).wrap_err("Couldn't parse command data")?;
+		command.handle(state, interaction).await
+	})
+}
+
+fn suggest<'a, C: Autocomplete>(
+	state: GlobalStateRef<'a>,
+	interaction: &'a Interaction,
+	options: Partial<'a>,
+) -> BoxFuture<'a, Vec<CommandOptionChoice>> {
+	Box::pin(C::autocomplete(state, interaction, options))
+}
+
+/// A user or message context menu command, registered with [Route::context_menu]
+pub(crate) trait ContextMenu: Sized + Send {
+	/// Shown in the context menu, can have spaces and capitals
+	const NAME: &'static str;
+	/// [CommandType::User] or [CommandType::Message]
+	const KIND: CommandType;
+
+	fn parse(data: CommandData) -> Result<Self>;
+
+	/// Errors are logged and shown to the user as a [UserFacingErr]
+	fn handle(
+		self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// The user a [CommandType::User] context menu was opened on
+pub(crate) fn target_user(mut data: CommandData) -> Result<User> {
+	let target = data
+		.target_id
+		.ok_or_else(|| eyre!("Context menu without a target"))?;
+	data.resolved
+		.as_mut()
+		.and_then(|resolved| resolved.users.remove(&target.cast()))
+		.ok_or_else(|| eyre!("Context menu's target user wasn't resolved"))
+}
+
+/// The message a [CommandType::Message] context menu was opened on
+pub(crate) fn target_message(mut data: CommandData) -> Result<Message> {
+	let target = data
+		.target_id
+		.ok_or_else(|| eyre!("Context menu without a target"))?;
+	data.resolved
+		.as_mut()
+		.and_then(|resolved| resolved.messages.remove(&target.cast()))
+		.ok_or_else(|| eyre!("Context menu's target message wasn't resolved"))
+}
+
+/// Handles presses of buttons with a custom ID of `"{PREFIX}:{args}"`,
+/// which survive restarts since they're only parsed when pressed
+pub(crate) trait Press: Sized + Send {
+	const PREFIX: &'static str;
+
+	/// Has to fit in a custom ID with [Press::PREFIX], 100 characters
+	fn args(&self) -> String;
+
+	fn parse(args: &str) -> Result<Self>;
+
+	fn custom_id(&self) -> String {
+		format!("{}:{}", Self::PREFIX, self.args())
+	}
+
+	/// Errors are logged and shown to the user as a [UserFacingErr]
+	fn handle(
+		self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+	) -> impl Future<Output = Result<()>> + Send;
+}
+
+type PressHandler = for<'a> fn(GlobalStateRef<'a>, Interaction, String) -> BoxFuture<'a>;
+
+pub(crate) struct ComponentRoute {
+	pub(super) prefix: &'static str,
+	middleware: &'static [Middleware],
+	handler: PressHandler,
+}
+
+impl ComponentRoute {
+	pub fn new<P: Press>(middleware: &'static [Middleware]) -> ComponentRoute {
+		ComponentRoute {
+			prefix: P::PREFIX,
+			middleware,
+			handler: parse_and_press::<P>,
+		}
+	}
+}
+
+fn parse_and_press<P: Press>(
+	state: GlobalStateRef<'_>,
+	interaction: Interaction,
+	args: String,
+) -> BoxFuture<'_> {
+	Box::pin(async move {
+		let press = P::parse(&args).wrap_err("Couldn't parse button's custom ID")?;
+		press.handle(state, interaction).await
+	})
+}
+
+pub(crate) struct Router {
+	routes: Vec<Route>,
+	pub(super) components: Vec<ComponentRoute>,
+}
+
+/// A matched route and what it's handling
+enum Target {
+	Command(&'static Route, CommandData),
+	Component(&'static ComponentRoute, String),
+}
+
+impl Router {
+	pub fn new(
+		routes: impl IntoIterator<Item = Route>,
+		components: impl IntoIterator<Item = ComponentRoute>,
+	) -> Router {
+		Router {
+			routes: routes.into_iter().collect(),
+			components: components.into_iter().collect(),
+		}
+	}
+
+	pub fn commands(&self, scope: Scope) -> Vec<Command> {
+		self.routes
+			.iter()
+			.filter(|route| route.scope == scope)
+			.map(|route| route.command.clone())
+			.collect()
+	}
+
+	/// Runs the command's middleware then its handler.
+	/// Errors and panics are reported to the user, only failing to report them is returned
+	pub async fn dispatch(
+		&'static self,
+		state: GlobalState,
+		interaction: Interaction,
+		data: CommandData,
+	) -> Result<()> {
+		let Some(route) = self
+			.routes
+			.iter()
+			.find(|route| route.command.name == data.name)
+		else {
+			bail!("unknown command: {}", data.name);
+		};
+		Target::Command(route, data).run(state, interaction).await
+	}
+
+	/// The same as [Router::dispatch] for a button press
+	pub async fn press(
+		&'static self,
+		state: GlobalState,
+		interaction: Interaction,
+		custom_id: &str,
+	) -> Result<()> {
+		let (prefix, args) = custom_id.split_once(':').unwrap_or((custom_id, ""));
+		let Some(route) = self.components.iter().find(|route| route.prefix == prefix) else {
+			bail!("unknown component: {custom_id}");
+		};
+		Target::Component(route, args.to_owned())
+			.run(state, interaction)
+			.await
+	}
+}
+
+impl Target {
+	/// Supervised in its own task, which is aborted if [crate::runner] stops waiting for it
+	/// unless it's a faucet already submitted to Salt, see [AbortUnlessSubmitted]
+	async fn run(self, state: GlobalState, interaction: Interaction) -> Result<()> {
+		let supervisor = tokio::spawn(
+			self.supervise(state.clone(), interaction.clone())
+				.in_current_span(),
+		);
+		let _abort = AbortUnlessSubmitted {
+			supervisor: supervisor.abort_handle(),
+			state,
+			interaction,
+		};
+		supervisor
+			.await
+			.wrap_err("Reporting a command's error panicked")?
+	}
+
+	async fn supervise(self, state: GlobalState, interaction: Interaction) -> Result<()> {
+		let task_state = state.clone();
+		let task_interaction = interaction.clone();
+		// in its own task so a panic is reported instead of silently dropping the interaction,
+		// in a set so it's aborted along with the supervisor
+		let mut handler = tokio::task::JoinSet::new();
+		handler.spawn(
+			async move { self.handle(task_state.get(), task_interaction).await }.in_current_span(),
+		);
+		let Some(res) = handler.join_next().await else {
+			bail!("Command handler wasn't spawned");
+		};
+		let err = match res {
+			Ok(Ok(())) => return Ok(()),
+			Ok(Err(err)) => err,
+			Err(err) => color_eyre::Report::new(err).wrap_err("Command handler panicked"),
+		};
+
+		let state = state.get();
+		let user_err = UserFacingErr::internal(CorrelationId::of(interaction.id), &err);
+		let visibility = reply_visibility(state, &interaction);
+		// the handler may or may not have responded before failing
+		if follow_up_as(state, &interaction, visibility, user_err.to_string())
+			.await
+			.is_err()
+		{
+			respond_as(state, &interaction, visibility, user_err.to_string())
+				.await
+				.wrap_err("Couldn't send internal error message")
+				.with_note(|| format!("Original internal error: {err}"))?;
+		}
+		Ok(())
+	}
+
+	async fn handle(self, state: GlobalStateRef<'_>, interaction: Interaction) -> Result<()> {
+		let (middleware, subcommand, request) = match &self {
+			Target::Command(route, data) => (
+				route.middleware,
+				subcommand(data).to_owned(),
+				format!("/{}", data.name),
+			),
+			Target::Component(route, _) => (
+				route.middleware,
+				route.prefix.to_owned(),
+				format!("{} button", route.prefix),
+			),
+		};
+		let Some(passed) = check(state, &interaction, middleware, &subcommand, &request).await?
+		else {
+			return Ok(());
+		};
+
+		let guild_id = interaction.guild_id;
+		let res = match self {
+			Target::Command(route, data) => (route.handler)(state, interaction, data).await,
+			Target::Component(route, args) => (route.handler)(state, interaction, args).await,
+		};
+		if let Some(user) = passed.admin {
+			match &res {
+				Ok(()) => state.audit.admin(guild_id, user, &subcommand, "ok"),
+				Err(err) => state
+					.audit
+					.admin(guild_id, user, &subcommand, format!("error: {err}")),
+			}
+		}
+		res
+	}
+}
+
+/// Shown when an interaction is aborted by [AbortUnlessSubmitted]
+const CANCELLED: &str = "This took too long and was cancelled, please try again";
+
+/// Dropped once the interaction is finished, or when [crate::runner] times out or shuts down.
+/// Faucets submitted to Salt are left to finish within [crate::cancel::Submitted::TIMEOUT]
+/// so their outcome is still recorded and reported, everything else is aborted
+struct AbortUnlessSubmitted {
+	supervisor: tokio::task::AbortHandle,
+	state: GlobalState,
+	interaction: Interaction,
+}
+
+impl Drop for AbortUnlessSubmitted {
+	fn drop(&mut self) {
+		if self.supervisor.is_finished()
+			|| self
+				.state
+				.get()
+				.cancellable
+				.is_submitted(self.interaction.id)
+		{
+			return;
+		}
+		warn!("Aborting an interaction that's still running");
+		self.supervisor.abort();
+		let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+			return;
+		};
+		let (state, interaction) = (self.state.clone(), self.interaction.clone());
+		runtime.spawn(
+			async move {
+				// best effort, it may not have been responded to yet
+				let res = state
+					.get()
+					.client
+					.interaction(interaction.application_id)
+					.update_response(&interaction.token)
+					.content(Some(CANCELLED))
+					.components(Some(&[]))
+					.await;
+				if let Err(err) = res {
+					debug!(?err, "Couldn't tell the user an interaction was aborted");
+				}
+			}
+			.in_current_span(),
+		);
+	}
+}
+
+impl Router {
+	/// Discord only shows the first 25 suggestions
+	const MAX_CHOICES: usize = 25;
+
+	/// Always responds, with no suggestions if they couldn't be found
+	pub async fn autocomplete(
+		&self,
+		state: GlobalStateRef<'_>,
+		interaction: Interaction,
+		data: CommandData,
+	) -> Result<()> {
+		let route = self
+			.routes
+			.iter()
+			.find(|route| route.command.name == data.name);
+		let choices = match (
+			route.and_then(|route| route.autocomplete),
+			Partial::new(&data),
+		) {
+			(Some(autocomplete), Some(options)) => autocomplete(state, &interaction, options)
+				.await
+				.unwrap_or_else(|err| {
+					warn!(?err, "Couldn't autocomplete a command");
+					Vec::new()
+				}),
+			_ => {
+				warn!(command = %data.name, "Autocomplete for a command without suggestions");
+				Vec::new()
+			}
+		};
+		state
+			.client
+			.interaction(interaction.application_id)
+			.create_response(
+				interaction.id,
+				&interaction.token,
+				&InteractionResponse {
+					kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+					data: Some(
+						InteractionResponseDataBuilder::new()
+							.choices(choices.into_iter().take(Self::MAX_CHOICES))
+							.build(),
+					),
+				},
+			)
+			.await
+			.wrap_err("Couldn't send autocomplete suggestions")
+			.map(|_| ())
+	}
+}
+
+/// Held while the handler runs
+struct Passed<'a> {
+	_spam_guard: Option<Guard<'a>>,
+	/// Who to audit the outcome for
+	admin: Option<Id<UserMarker>>,
+}
+
+/// [None] if refused, after telling the user why
+async fn check<'a>(
+	state: GlobalStateRef<'a>,
+	interaction: &Interaction,
+	middleware: &[Middleware],
+	subcommand: &str,
+	request: &str,
+) -> Result<Option<Passed<'a>>> {
+	let mut passed = Passed {
+		_spam_guard: None,
+		admin: None,
+	};
+	for middleware in middleware {
+		let refusal = match *middleware {
+			Middleware::GuildOnly(reason) => {
+				interaction.guild_id.is_none().then(|| reason.to_owned())
+			}
+			Middleware::FaucetChannel => state
+				.env
+				.guild(interaction.guild_id)
+				.and_then(|guild| {
+					guild
+						.check_faucet_channel(interaction.channel.as_ref())
+						.err()
+				})
+				.map(|err| err.to_string()),
+			Middleware::Admin | Middleware::AdminAs(_) => {
+				let permissions = match *middleware {
+					Middleware::AdminAs(permissions) => permissions,
+					_ => subcommand,
+				};
+				let user = interaction
+					.author_id()
+					.ok_or_else(|| eyre!("Admin command invoked without a user"))?;
+				let roles = interaction
+					.member
+					.as_ref()
+					.map(|member| member.roles.as_slice())
+					.unwrap_or_default();
+				let guild_admin_roles = state
+					.env
+					.guild(interaction.guild_id)
+					.map(|guild| guild.admin_roles.as_slice())
+					.unwrap_or_default();
+				match state
+					.env
+					.admin
+					.check(permissions, user, roles, guild_admin_roles)
+				{
+					Ok(()) => {
+						passed.admin = Some(user);
+						None
+					}
+					Err(err) => {
+						warn!(%user, %subcommand, "Refusing an admin command");
+						state
+							.audit
+							.admin(interaction.guild_id, user, subcommand, "refused");
+						Some(err.to_string())
+					}
+				}
+			}
+			Middleware::SpamFilter { except } if !except.contains(&subcommand) => {
+				let user = interaction
+					.author_id()
+					.ok_or_else(|| eyre!("Command invoked without a user"))?;
+				match state.per_user_spam_filters.engage(user) {
+					Ok(guard) => {
+						passed._spam_guard = Some(guard);
+						None
+					}
+					Err(err) => {
+						state.metrics.spam_filter_rejections.inc();
+						Some(err.to_string())
+					}
+				}
+			}
+			Middleware::SpamFilter { .. } => None,
+		};
+		if let Some(refusal) = refusal {
+			// admin refusals are already audited as such
+			if !matches!(middleware, Middleware::Admin | Middleware::AdminAs(_))
+				&& let Some(user) = interaction.author_id()
+			{
+				state
+					.audit
+					.refused(interaction.guild_id, user, request, &refusal);
+			}
+			let visibility = reply_visibility(state, interaction);
+			respond_as(state, interaction, visibility, refusal).await?;
+			return Ok(None);
+		}
+	}
+	Ok(Some(passed))
+}
+
+/// The first option, which is the subcommand for commands made of subcommands,
+/// or the name of a context menu which has no options
+fn subcommand(data: &CommandData) -> &str {
+	match data.kind {
+		CommandType::ChatInput => data
+			.options
+			.first()
+			.map(|option| option.name.as_str())
+			.unwrap_or_default(),
+		_ => &data.name,
+	}
+}
+
+/// Refusals and errors are ephemeral unless the guild's [crate::guilds::Privacy] says otherwise
+fn reply_visibility(state: GlobalStateRef<'_>, interaction: &Interaction) -> Visibility {
+	state
+		.env
+		.guild(interaction.guild_id)
+		.map(|guild| guild.privacy)
+		.unwrap_or_default()
+		.visibility(Visibility::Ephemeral)
+}
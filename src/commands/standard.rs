@@ -3,10 +3,10 @@ use alloy::{
 	providers::{Provider as _, ProviderBuilder},
 };
 use twilight_interactions::command::{CommandModel, CreateCommand};
-use twilight_model::application::interaction::{Interaction, application_command::CommandData};
+use twilight_model::application::interaction::Interaction;
 
 use crate::{
-	commands::{defer, follow_up, respond},
+	commands::{defer, follow_up, respond, router::Handle},
 	common::GlobalStateRef,
 	prelude::*,
 };
@@ -26,19 +26,9 @@ pub enum SomniaStandardCommand {
 #[command(name = "balance")]
 pub struct Balance;
 
-impl SomniaStandardCommand {
-	pub async fn handle(
-		state: GlobalStateRef<'_>,
-		interaction: Interaction,
-		data: CommandData,
-	) -> color_eyre::Result<()> {
-		let this = SomniaStandardCommand::from_interaction(data.into())
-			.wrap_err("Couldn't parse command data")?;
-		this._handle(state, interaction).await
-	}
-
-	async fn _handle(
-		&self,
+impl Handle for SomniaStandardCommand {
+	async fn handle(
+		self,
 		state: GlobalStateRef<'_>,
 		interaction: Interaction,
 	) -> color_eyre::Result<()> {
@@ -5,56 +5,111 @@ use twilight_http::Client;
 use ystd::sync::Mutex;
 
 use crate::{
-	env::Env, per_user_spam_filter::PerUserSpamFilter, prelude::*, ratelimits::RateLimits,
+	audit::Audit,
+	cancel::Cancellable,
+	env::Env,
+	health::Health,
+	metrics::Metrics,
+	per_user_spam_filter::PerUserSpamFilter,
+	prelude::*,
+	ratelimits::RateLimits,
+	reload::{LiveConfig, Snapshot},
+	secrets::PrivateKey,
+	shutdown::Drain,
 };
 
 /// Cheap to clone
 #[derive(Clone)]
 pub struct GlobalState {
 	client: Arc<Client>,
+	/// Fixed for the lifetime of this value, see [GlobalState::latest]
 	env: Arc<Env>,
+	config: Arc<LiveConfig>,
+	private_key: Arc<PrivateKey>,
 	ratelimits: Arc<Mutex<RateLimits>>,
 	per_user_spam_filters: Arc<PerUserSpamFilter>,
+	cancellable: Arc<Cancellable>,
 	kill_now: Arc<Notify>,
 	shutting_down: Arc<AtomicBool>,
+	drain: Arc<Drain>,
+	audit: Arc<Audit>,
+	metrics: Arc<Metrics>,
+	health: Arc<Health>,
 }
 
 #[derive(Clone, Copy)]
 pub struct GlobalStateRef<'a> {
 	pub client: &'a Client,
 	pub env: &'a Env,
+	pub config: &'a LiveConfig,
+	/// Loaded from [Env::signer]
+	pub private_key: &'a PrivateKey,
 	pub ratelimits: &'a Mutex<RateLimits>,
 	pub per_user_spam_filters: &'a PerUserSpamFilter,
+	pub cancellable: &'a Cancellable,
 	pub kill_now: &'a Notify,
 	pub shutting_down: &'a AtomicBool,
+	pub drain: &'a Drain,
+	pub audit: &'a Audit,
+	pub metrics: &'a Metrics,
+	pub health: &'a Health,
 }
 
 impl GlobalState {
 	pub fn new(
 		client: Arc<Client>,
+		config_path: Option<Utf8PathBuf>,
 		env: Env,
+		private_key: PrivateKey,
 		ratelimits: RateLimits,
 		kill_now: Notify,
 		shutting_down: Arc<AtomicBool>,
+		metrics: Arc<Metrics>,
+		health: Arc<Health>,
 	) -> Result<Self> {
+		let snapshot = Snapshot::new(client.clone(), env);
 		Ok(GlobalState {
+			env: snapshot.env.clone(),
+			audit: snapshot.audit.clone(),
+			config: Arc::new(LiveConfig::new(config_path, client.clone(), snapshot)),
 			client,
-			env: Arc::new(env),
+			private_key: Arc::new(private_key),
 			ratelimits: Arc::new(Mutex::new(ratelimits)),
 			per_user_spam_filters: Arc::new(PerUserSpamFilter::default()),
+			cancellable: Arc::new(Cancellable::default()),
 			kill_now: Arc::new(kill_now),
 			shutting_down,
+			drain: Arc::new(Drain::default()),
+			metrics,
+			health,
 		})
 	}
 
+	/// With the most recently reloaded config, call when starting to handle something new
+	pub fn latest(&self) -> GlobalState {
+		let Snapshot { env, audit } = self.config.latest();
+		GlobalState {
+			env,
+			audit,
+			..self.clone()
+		}
+	}
+
 	pub fn get(&self) -> GlobalStateRef<'_> {
 		GlobalStateRef {
 			env: &self.env,
+			config: &self.config,
+			private_key: &self.private_key,
 			client: &self.client,
 			ratelimits: &self.ratelimits,
 			per_user_spam_filters: &self.per_user_spam_filters,
+			cancellable: &self.cancellable,
 			kill_now: &self.kill_now,
 			shutting_down: &self.shutting_down,
+			drain: &self.drain,
+			audit: &self.audit,
+			metrics: &self.metrics,
+			health: &self.health,
 		}
 	}
 }
@@ -63,11 +118,18 @@ impl<'a> GlobalStateRef<'a> {
 	pub fn reborrow(&self) -> GlobalStateRef<'_> {
 		GlobalStateRef {
 			env: self.env,
+			config: self.config,
+			private_key: self.private_key,
 			client: self.client,
 			ratelimits: self.ratelimits,
 			per_user_spam_filters: self.per_user_spam_filters,
+			cancellable: self.cancellable,
 			kill_now: self.kill_now,
 			shutting_down: self.shutting_down,
+			drain: self.drain,
+			audit: self.audit,
+			metrics: self.metrics,
+			health: self.health,
 		}
 	}
 }
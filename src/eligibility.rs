@@ -0,0 +1,217 @@
+use time::OffsetDateTime;
+use twilight_model::{
+	id::{
+		Id,
+		marker::{RoleMarker, UserMarker},
+	},
+	util::Timestamp,
+};
+
+use crate::prelude::*;
+
+/// Sybil resistance rules, checked before any ratelimiting happens.
+/// Every rule is disabled by default
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct Eligibility {
+	/// Minimum age of the discord account, derived from the user id snowflake
+	pub min_account_age_days: u32,
+	/// Minimum time since the user joined the guild the command was sent from
+	pub min_membership_hours: u32,
+	/// If non-empty, the user must have at least one of these roles
+	pub required_roles: Vec<Id<RoleMarker>>,
+	/// Users who are never allowed to use the faucet
+	pub denylist: Vec<Id<UserMarker>>,
+}
+
+/// What [Eligibility] needs to know about the person calling the faucet
+pub struct Applicant<'a> {
+	pub discord_id: Id<UserMarker>,
+	pub joined_at: Option<Timestamp>,
+	pub roles: &'a [Id<RoleMarker>],
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IneligibleErr {
+	#[error(
+		"You aren't allowed to use the faucet. If you think this is a mistake, please contact a moderator"
+	)]
+	Denylisted,
+
+	#[error(
+		"Your discord account is too new to use the faucet, accounts must be at least {min_days} day(s) old. Please try again in {}",
+		format_duration(.remaining)
+	)]
+	AccountTooNew {
+		min_days: u32,
+		remaining: time::Duration,
+	},
+
+	#[error(
+		"You've only just joined this server! Members must wait {min_hours} hour(s) before using the faucet. Please try again in {}",
+		format_duration(.remaining)
+	)]
+	MembershipTooNew {
+		min_hours: u32,
+		remaining: time::Duration,
+	},
+
+	#[error("Couldn't work out when you joined this server, which is required to use the faucet")]
+	MembershipUnknown,
+
+	#[error("You need one of these roles to use the faucet: {}", mention_roles(.0))]
+	MissingRole(Vec<Id<RoleMarker>>),
+}
+
+fn format_duration(duration: &time::Duration) -> String {
+	let days = duration.whole_days();
+	let hours = duration.whole_hours() % 24;
+	let minutes = duration.whole_minutes() % 60;
+	if days > 0 {
+		format!("{days} day(s) {hours} hour(s)")
+	} else {
+		format!("{hours} hour(s) {minutes} minute(s)")
+	}
+}
+
+fn mention_roles(roles: &[Id<RoleMarker>]) -> String {
+	roles
+		.iter()
+		.map(|role| format!("<@&{role}>"))
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+/// Discord epoch, the first second of 2015
+const DISCORD_EPOCH_MS: u64 = 1420070400000;
+
+/// When anything with a snowflake ID was created, from its top 42 bits
+/// https://discord.com/developers/docs/reference#snowflakes
+pub fn snowflake_created<T>(id: Id<T>) -> OffsetDateTime {
+	let unix_ms = (id.get() >> 22) + DISCORD_EPOCH_MS;
+	OffsetDateTime::from_unix_timestamp_nanos(unix_ms as i128 * 1_000_000)
+		.expect("snowflake timestamps are always in range")
+}
+
+/// When the discord account was created
+pub fn account_created(discord_id: Id<UserMarker>) -> OffsetDateTime {
+	snowflake_created(discord_id)
+}
+
+impl Eligibility {
+	pub fn check(&self, applicant: &Applicant<'_>) -> Result<(), IneligibleErr> {
+		self.check_at(OffsetDateTime::now_utc(), applicant)
+	}
+
+	fn check_at(
+		&self,
+		now: OffsetDateTime,
+		applicant: &Applicant<'_>,
+	) -> Result<(), IneligibleErr> {
+		if self.denylist.contains(&applicant.discord_id) {
+			return Err(IneligibleErr::Denylisted);
+		}
+
+		if self.min_account_age_days > 0 {
+			let min_age = time::Duration::days(self.min_account_age_days.into());
+			let age = now - account_created(applicant.discord_id);
+			if age < min_age {
+				return Err(IneligibleErr::AccountTooNew {
+					min_days: self.min_account_age_days,
+					remaining: min_age - age,
+				});
+			}
+		}
+
+		if self.min_membership_hours > 0 {
+			let min_membership = time::Duration::hours(self.min_membership_hours.into());
+			let joined_at = applicant
+				.joined_at
+				.and_then(|joined_at| {
+					OffsetDateTime::from_unix_timestamp_nanos(joined_at.as_micros() as i128 * 1_000)
+						.ok()
+				})
+				.ok_or(IneligibleErr::MembershipUnknown)?;
+			let membership = now - joined_at;
+			if membership < min_membership {
+				return Err(IneligibleErr::MembershipTooNew {
+					min_hours: self.min_membership_hours,
+					remaining: min_membership - membership,
+				});
+			}
+		}
+
+		if !self.required_roles.is_empty()
+			&& !self
+				.required_roles
+				.iter()
+				.any(|role| applicant.roles.contains(role))
+		{
+			return Err(IneligibleErr::MissingRole(self.required_roles.clone()));
+		}
+
+		Ok(())
+	}
+}
+
+#[test]
+fn snowflake_account_created() {
+	// example from https://discord.com/developers/docs/reference#snowflakes
+	let created = account_created(Id::new(175928847299117063));
+	assert_eq!(created.unix_timestamp(), 1462015105);
+}
+
+#[test]
+fn eligibility_rules() {
+	let now = OffsetDateTime::now_utc();
+	let role = Id::new(1);
+	let eligibility = Eligibility {
+		min_account_age_days: 30,
+		min_membership_hours: 24,
+		required_roles: vec![role],
+		denylist: vec![Id::new(175928847299117063)],
+	};
+	let joined_at =
+		|ago: time::Duration| Some(Timestamp::from_secs((now - ago).unix_timestamp()).unwrap());
+
+	let old = Applicant {
+		discord_id: Id::new(175928847299117064),
+		joined_at: joined_at(time::Duration::DAY * 2),
+		roles: &[role],
+	};
+	assert!(eligibility.check_at(now, &old).is_ok());
+
+	let denied = Applicant {
+		discord_id: Id::new(175928847299117063),
+		..old
+	};
+	assert!(matches!(
+		eligibility.check_at(now, &denied),
+		Err(IneligibleErr::Denylisted)
+	));
+
+	let fresh_ms = (now.unix_timestamp() as u64 * 1000) - DISCORD_EPOCH_MS;
+	let fresh = Applicant {
+		discord_id: Id::new(fresh_ms << 22),
+		..old
+	};
+	assert!(matches!(
+		eligibility.check_at(now, &fresh),
+		Err(IneligibleErr::AccountTooNew { .. })
+	));
+
+	let just_joined = Applicant {
+		joined_at: joined_at(time::Duration::HOUR),
+		..old
+	};
+	assert!(matches!(
+		eligibility.check_at(now, &just_joined),
+		Err(IneligibleErr::MembershipTooNew { .. })
+	));
+
+	let no_role = Applicant { roles: &[], ..old };
+	assert!(matches!(
+		eligibility.check_at(now, &no_role),
+		Err(IneligibleErr::MissingRole(_))
+	));
+}
@@ -1,12 +1,25 @@
+use std::collections::HashMap;
+
 use alloy::primitives::Address;
+use camino::Utf8Path;
+use twilight_model::id::{
+	Id,
+	marker::{ApplicationMarker, GuildMarker},
+};
 use url::Url;
 
-use crate::prelude::*;
+use crate::{
+	chains::proof::ProofOfActivity, eligibility::Eligibility, guilds::GuildConfig,
+	http::HttpConfig, logs::LogsConfig, permissions::AdminPermissions, prelude::*,
+	secrets::SecretSource,
+};
 
 #[derive(serde::Deserialize, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub struct Env {
-	pub private_key: String,
+	/// Where the Salt signer's private key is read from, the `PRIVATE_KEY` environment variable by default
+	#[serde(default)]
+	pub signer: SecretSource,
 
 	pub bot_application_id: String,
 	pub bot_token: String,
@@ -16,44 +29,184 @@ pub struct Env {
 	pub sepolia_ethereum_rpc_endpoint: Url,
 	pub polygon_amoy_rpc_endpoint: Url,
 	pub faucet_testnet_salt_account_address: Address,
+
+	#[serde(default)]
+	pub eligibility: Eligibility,
+	#[serde(default)]
+	pub proof_of_activity: Option<ProofOfActivity>,
+	#[serde(default)]
+	pub admin: AdminPermissions,
+	#[serde(default)]
+	pub guilds: HashMap<Id<GuildMarker>, GuildConfig>,
+	#[serde(default)]
+	pub logs: LogsConfig,
+	/// Serves `/healthz`, `/readyz` and `/metrics` if set
+	#[serde(default)]
+	pub http: Option<HttpConfig>,
 }
 
-/// Only statically includes toml if building for release,
-/// for better error messages
-/// Can refactor this if wanted
-#[allow(dead_code)]
 impl Env {
+	/// [None] for DMs and unconfigured guilds
+	pub fn guild(&self, guild_id: Option<Id<GuildMarker>>) -> Option<&GuildConfig> {
+		self.guilds.get(&guild_id?)
+	}
+
+	pub fn admin_guilds(&self) -> impl Iterator<Item = Id<GuildMarker>> + '_ {
+		self.guilds
+			.iter()
+			.filter(|(_, guild)| guild.admin)
+			.map(|(guild_id, _)| *guild_id)
+	}
+}
+
+/// Loaded at runtime so one build can run anywhere, nothing is baked into the binary.
+/// Keys in the config file take precedence over environment variables of the same name,
+/// which take precedence over defaults
+impl Env {
+	/// Top level keys that can be set as environment variables
+	const ENV_VARS: &[&str] = &[
+		"BOT_APPLICATION_ID",
+		"BOT_TOKEN",
+		"SOMNIA_SHANNON_RPC_ENDPOINT",
+		"SEPOLIA_ARBITRUM_RPC_ENDPOINT",
+		"SEPOLIA_ETHEREUM_RPC_ENDPOINT",
+		"POLYGON_AMOY_RPC_ENDPOINT",
+		"FAUCET_TESTNET_SALT_ACCOUNT_ADDRESS",
+	];
+
+	/// `dev.env.toml` in the repo for debug builds, `env.toml` in the working directory otherwise
+	pub fn default_path() -> Utf8PathBuf {
+		if cfg!(debug_assertions) {
+			Utf8PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("dev.env.toml")
+		} else {
+			Utf8PathBuf::from("env.toml")
+		}
+	}
+
+	/// Uses [Env::default_path] if it exists
 	pub async fn get() -> Result<Env> {
-		#[cfg(debug_assertions)]
-		return Self::from_local_dev_env().await;
-		#[cfg(not(debug_assertions))]
-		Self::from_statically_included()
+		Self::load(None).await
 	}
 
-	async fn from_local_dev_env() -> Result<Env> {
-		let path = Utf8PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("dev.env.toml");
-		let file = ystd::fs::read_to_string(path)
-			.await
-			.wrap_err("Couldn't read dev.env.toml")?;
-		let env: Env =
-			toml::from_str(&file).wrap_err("dev.env.toml not valid toml or missing required key")?;
+	/// Without a `path`, uses [Env::default_path] if it exists, otherwise only environment variables
+	pub async fn load(path: Option<&Utf8Path>) -> Result<Env> {
+		let default_path = Self::default_path();
+		let path = match path {
+			Some(path) => Some(path),
+			None if tokio::fs::try_exists(&default_path).await.unwrap_or(false) => {
+				Some(default_path.as_path())
+			}
+			None => None,
+		};
+		let file = match path {
+			Some(path) => {
+				let file = ystd::fs::read_to_string(path)
+					.await
+					.wrap_err_with(|| format!("Couldn't read config file {path}"))?;
+				toml::from_str(&file).wrap_err_with(|| format!("{path} isn't valid toml"))?
+			}
+			None => toml::Table::new(),
+		};
+		Self::from_sources(file, std::env::vars())
+	}
+
+	fn from_sources(
+		file: toml::Table,
+		vars: impl IntoIterator<Item = (String, String)>,
+	) -> Result<Env> {
+		let mut table: toml::Table = vars
+			.into_iter()
+			.filter(|(key, _)| Self::ENV_VARS.contains(&key.as_str()))
+			.map(|(key, value)| (key, toml::Value::String(value)))
+			.collect();
+		if file.contains_key("PRIVATE_KEY") {
+			bail!(
+				"PRIVATE_KEY can't be set in the config file, set the PRIVATE_KEY environment variable or configure [SIGNER]"
+			);
+		}
+		table.extend(file);
+		let env: Env = toml::Value::Table(table)
+			.try_into()
+			.wrap_err("Config is missing a required key or has an invalid value")?;
+		env.validate()?;
 		Ok(env)
 	}
 
-	#[cfg(not(debug_assertions))]
-	fn from_statically_included() -> Result<Env> {
-		static_toml::static_toml!(
-			static ENV = include_toml!("env.toml");
-		);
-		Ok(Env {
-			bot_application_id: ENV.bot_application_id.into(),
-			bot_token: ENV.bot_token.into(),
-			somnia_shannon_rpc_endpoint: ENV.somnia_shannon_rpc_endpoint.parse()?,
-			sepolia_arbitrum_rpc_endpoint: ENV.sepolia_arbitrum_rpc_endpoint.parse()?,
-			sepolia_ethereum_rpc_endpoint: ENV.sepolia_ethereum_rpc_endpoint.parse()?,
-			polygon_amoy_rpc_endpoint: ENV.polygon_amoy_rpc_endpoint.parse()?,
-			faucet_testnet_salt_account_address: ENV.faucet_testnet_salt_account_address.parse()?,
-			private_key: ENV.private_key.into(),
-		})
+	/// Checks what serde can't, reporting every problem at once
+	fn validate(&self) -> Result<()> {
+		let mut problems = Vec::new();
+		if self.bot_token.trim().is_empty() {
+			problems.push("BOT_TOKEN is empty".to_owned());
+		}
+		if self
+			.bot_application_id
+			.parse::<Id<ApplicationMarker>>()
+			.is_err()
+		{
+			problems.push("BOT_APPLICATION_ID isn't a discord id".to_owned());
+		}
+		for (key, url) in [
+			(
+				"SOMNIA_SHANNON_RPC_ENDPOINT",
+				&self.somnia_shannon_rpc_endpoint,
+			),
+			(
+				"SEPOLIA_ARBITRUM_RPC_ENDPOINT",
+				&self.sepolia_arbitrum_rpc_endpoint,
+			),
+			(
+				"SEPOLIA_ETHEREUM_RPC_ENDPOINT",
+				&self.sepolia_ethereum_rpc_endpoint,
+			),
+			("POLYGON_AMOY_RPC_ENDPOINT", &self.polygon_amoy_rpc_endpoint),
+		] {
+			if !matches!(url.scheme(), "http" | "https") {
+				problems.push(format!("{key} isn't an http(s) url"));
+			}
+		}
+		if let Some(proof) = &self.proof_of_activity
+			&& proof.min_balance.is_none()
+			&& proof.min_nonce.is_none()
+		{
+			problems.push("PROOF_OF_ACTIVITY needs MIN_BALANCE or MIN_NONCE".to_owned());
+		}
+
+		if problems.is_empty() {
+			Ok(())
+		} else {
+			Err(eyre!("Invalid config:\n- {}", problems.join("\n- ")))
+		}
 	}
 }
+
+#[test]
+fn env_sources() {
+	let file: toml::Table = toml::from_str(
+		r#"
+		BOT_TOKEN = "from file"
+		SOMNIA_SHANNON_RPC_ENDPOINT = "https://dream-rpc.somnia.network/"
+		SEPOLIA_ARBITRUM_RPC_ENDPOINT = "https://sepolia-rollup.arbitrum.io/rpc"
+		SEPOLIA_ETHEREUM_RPC_ENDPOINT = "https://sepolia.drpc.org"
+		POLYGON_AMOY_RPC_ENDPOINT = "https://rpc-amoy.polygon.technology"
+		FAUCET_TESTNET_SALT_ACCOUNT_ADDRESS = "0x33E7fAB0a8a5da1A923180989bD617c9c2D1C493"
+		"#,
+	)
+	.expect("to parse");
+	let vars = |application_id: &str| {
+		[
+			("BOT_TOKEN", "from env"),
+			("BOT_APPLICATION_ID", application_id),
+			("UNRELATED", "ignored"),
+		]
+		.map(|(key, value)| (key.to_owned(), value.to_owned()))
+	};
+
+	let env = Env::from_sources(file.clone(), vars("1371363785985490975")).expect("to load");
+	assert_eq!(env.bot_token, "from file");
+	assert_eq!(env.bot_application_id, "1371363785985490975");
+
+	let Err(err) = Env::from_sources(file, vars("not an id")) else {
+		panic!("an invalid application id to be rejected");
+	};
+	assert!(err.to_string().contains("BOT_APPLICATION_ID"));
+}
@@ -0,0 +1,239 @@
+use std::{
+	collections::BTreeMap,
+	sync::{Mutex, atomic::Ordering},
+	time::{Duration, Instant},
+};
+
+use twilight_gateway::{CloseFrame, Config, ConfigBuilder, MessageSender, Session, Shard, ShardId};
+
+use crate::{common::GlobalState, prelude::*};
+
+/// Exponential backoff with jitter, so restarts don't hammer discord in lockstep
+#[derive(Debug)]
+pub struct Backoff {
+	base: Duration,
+	max: Duration,
+	attempts: u32,
+}
+
+impl Backoff {
+	pub const fn new(base: Duration, max: Duration) -> Backoff {
+		Backoff {
+			base,
+			max,
+			attempts: 0,
+		}
+	}
+
+	pub fn attempts(&self) -> u32 {
+		self.attempts
+	}
+
+	pub fn reset(&mut self) {
+		self.attempts = 0;
+	}
+
+	/// Between half and all of `base * 2^attempts`, capped at `max`
+	pub fn next_delay(&mut self) -> Duration {
+		let exponential = self
+			.base
+			.saturating_mul(2u32.saturating_pow(self.attempts))
+			.min(self.max);
+		self.attempts = self.attempts.saturating_add(1);
+		exponential.mul_f64(0.5 + fastrand::f64() / 2.0)
+	}
+}
+
+/// What a shard needs to resume its session instead of identifying again,
+/// so discord replays the events missed while it was disconnected
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Resume {
+	session: Session,
+	resume_url: Option<String>,
+	/// Sessions are only valid for the same number of shards
+	total: u32,
+}
+
+/// Kept across restarts of [crate::start::start], and saved to disk when shutting down
+/// so they survive the process restarting too
+#[derive(Debug, Default)]
+pub struct Sessions(Mutex<BTreeMap<u32, Resume>>);
+
+impl Sessions {
+	const PATH: &str = if cfg!(not(debug_assertions)) {
+		// hard coded for server
+		"/home/ah/Desktop/gateway-sessions.json"
+	} else {
+		concat!(env!("CARGO_MANIFEST_DIR"), "/gateway-sessions.json")
+	};
+
+	/// Starts without any sessions if there's no valid file
+	pub async fn read() -> Sessions {
+		let sessions = match tokio::fs::read_to_string(Self::PATH).await {
+			Ok(file) => serde_json::from_str(&file).unwrap_or_else(|err| {
+				warn!(%err, "Ignoring invalid gateway sessions file");
+				BTreeMap::new()
+			}),
+			Err(_) => BTreeMap::new(),
+		};
+		Sessions(Mutex::new(sessions))
+	}
+
+	pub async fn save(&self) -> Result<()> {
+		let data = serde_json::to_string(&*self.0.lock().or_poisoned())?;
+		tokio::fs::write(Self::PATH, data)
+			.await
+			.wrap_err("Couldn't save gateway sessions")
+	}
+
+	/// When the sessions were closed and can't be resumed
+	pub async fn clear(&self) -> Result<()> {
+		self.0.lock().or_poisoned().clear();
+		self.save().await
+	}
+
+	fn record(&self, shard: &Shard) {
+		let id = shard.id();
+		let mut sessions = self.0.lock().or_poisoned();
+		match shard.session() {
+			Some(session) => {
+				sessions.insert(
+					id.number(),
+					Resume {
+						session: session.clone(),
+						resume_url: shard.resume_url().map(ToOwned::to_owned),
+						total: id.total(),
+					},
+				);
+			}
+			None => {
+				sessions.remove(&id.number());
+			}
+		}
+	}
+
+	/// Resumes the shard's last session if there is one
+	pub fn configure(&self, id: ShardId, builder: ConfigBuilder) -> Config {
+		let resume = self
+			.0
+			.lock()
+			.or_poisoned()
+			.get(&id.number())
+			.filter(|resume| resume.total == id.total())
+			.cloned();
+		match resume {
+			Some(Resume {
+				session,
+				resume_url,
+				..
+			}) => {
+				info!(shard = %id, "Resuming the previous gateway session");
+				let builder = builder.session(session);
+				match resume_url {
+					Some(resume_url) => builder.resume_url(resume_url),
+					None => builder,
+				}
+				.build()
+			}
+			None => builder.build(),
+		}
+	}
+}
+
+/// Restarts shards that stop without tearing down the others
+pub struct Supervisor {
+	config: Config,
+	sessions: Arc<Sessions>,
+	/// Of each shard's current connection, replaced when a shard is restarted
+	senders: Mutex<BTreeMap<u32, MessageSender>>,
+}
+
+impl Supervisor {
+	/// Restarts sooner than this count as repeated failures
+	const HEALTHY_AFTER: Duration = Duration::from_secs(60 * 5);
+	/// Gives up on a shard, restarting the whole bot instead
+	const MAX_ATTEMPTS: u32 = 8;
+
+	pub fn new(config: Config, sessions: Arc<Sessions>) -> Supervisor {
+		Supervisor {
+			config,
+			sessions,
+			senders: Mutex::new(BTreeMap::new()),
+		}
+	}
+
+	fn shard(&self, id: ShardId) -> Shard {
+		let config = self
+			.sessions
+			.configure(id, ConfigBuilder::from(self.config.clone()));
+		Shard::with_config(id, config)
+	}
+
+	/// Every shard's current connection
+	pub fn close_all(&self, frame: CloseFrame<'static>) {
+		for sender in self.senders.lock().or_poisoned().values() {
+			// Ignore error if shard's already shutdown.
+			_ = sender.close(frame.clone());
+		}
+	}
+
+	/// Runs the shard until shutting down, restarting it with backoff if it stops before then.
+	/// Only errors once the shard keeps stopping
+	pub async fn supervise(self: Arc<Self>, state: GlobalState, mut shard: Shard) -> Result<()> {
+		let id = shard.id();
+		let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60 * 2));
+		loop {
+			self.senders
+				.lock()
+				.or_poisoned()
+				.insert(id.number(), shard.sender());
+			let started = Instant::now();
+			// in a set, so the runner is aborted along with this supervisor
+			let mut runner = tokio::task::JoinSet::new();
+			let runner_state = state.clone();
+			runner.spawn(async move {
+				crate::runner::runner(runner_state, &mut shard).await;
+				shard
+			});
+			let Some(res) = runner.join_next().await else {
+				bail!("Shard {id} runner wasn't spawned");
+			};
+			match &res {
+				Ok(shard) => self.sessions.record(shard),
+				Err(err) => error!(shard = %id, ?err, "Shard task panicked"),
+			}
+			if state.get().shutting_down.load(Ordering::Acquire) {
+				return Ok(());
+			}
+
+			state.get().health.shard(id.number(), false);
+			state.get().metrics.gateway_reconnect(id.number());
+			if started.elapsed() > Self::HEALTHY_AFTER {
+				backoff.reset();
+			}
+			if backoff.attempts() >= Self::MAX_ATTEMPTS {
+				bail!(
+					"Shard {id} stopped {} times in a row, giving up on it",
+					Self::MAX_ATTEMPTS
+				);
+			}
+			let delay = backoff.next_delay();
+			warn!(shard = %id, ?delay, "Shard stopped without shutting down, restarting it");
+			tokio::time::sleep(delay).await;
+			shard = self.shard(id);
+		}
+	}
+}
+
+#[test]
+fn backoff_delays() {
+	let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(10));
+	let delays: Vec<Duration> = (0..6).map(|_| backoff.next_delay()).collect();
+	for (attempt, delay) in delays.iter().enumerate() {
+		let max = Duration::from_secs(1 << attempt).min(Duration::from_secs(10));
+		assert!(*delay >= max / 2 && *delay <= max, "{attempt}: {delay:?}");
+	}
+	assert_eq!(backoff.attempts(), 6);
+	backoff.reset();
+	assert!(backoff.next_delay() <= Duration::from_secs(1));
+}
@@ -0,0 +1,89 @@
+use twilight_model::{
+	channel::Channel,
+	id::{
+		Id,
+		marker::{ChannelMarker, RoleMarker},
+	},
+};
+
+use crate::{commands::Visibility, prelude::*};
+
+/// Per discord server configuration, so the bot can be deployed to
+/// more than one community without code changes.
+/// Keyed by guild id in [crate::env::Env::guilds]
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct GuildConfig {
+	/// Registers the `/admin` commands in this guild
+	pub admin: bool,
+	/// Members with any of these roles can use every `/admin` subcommand
+	/// without a per-subcommand override
+	pub admin_roles: Vec<Id<RoleMarker>>,
+	/// Members with any of these roles aren't ratelimited
+	pub expanded_limit_roles: Vec<Id<RoleMarker>>,
+	/// If non-empty, faucet commands can only be used in these channels
+	pub faucet_channels: Vec<Id<ChannelMarker>>,
+	/// Where to post a record of every faucet attempt and admin action in this guild,
+	/// plus automatic actions if this is an admin guild
+	pub audit_channel: Option<Id<ChannelMarker>>,
+	/// Whether faucet replies are visible to everyone
+	pub privacy: Privacy,
+}
+
+/// Avoids publishing wallet addresses and errors in public channels
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct Privacy {
+	/// Overrides each command's default [Visibility] if set
+	pub ephemeral: Option<bool>,
+	/// When replies are ephemeral, still publish successful faucets publicly as social proof
+	pub public_success: bool,
+}
+
+impl Privacy {
+	pub fn visibility(&self, default: Visibility) -> Visibility {
+		match self.ephemeral {
+			Some(true) => Visibility::Ephemeral,
+			Some(false) => Visibility::Public,
+			None => default,
+		}
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("The faucet can't be used in this channel, please use {}", mention_channels(.0))]
+pub struct WrongChannelErr(Vec<Id<ChannelMarker>>);
+
+fn mention_channels(channels: &[Id<ChannelMarker>]) -> String {
+	channels
+		.iter()
+		.map(|channel| format!("<#{channel}>"))
+		.collect::<Vec<_>>()
+		.join(" or ")
+}
+
+impl GuildConfig {
+	/// Threads are allowed if their parent channel is
+	pub fn check_faucet_channel(&self, channel: Option<&Channel>) -> Result<(), WrongChannelErr> {
+		if self.faucet_channels.is_empty() {
+			return Ok(());
+		}
+		let allowed = channel.is_some_and(|channel| {
+			self.faucet_channels.contains(&channel.id)
+				|| channel
+					.parent_id
+					.is_some_and(|parent| self.faucet_channels.contains(&parent))
+		});
+		if allowed {
+			Ok(())
+		} else {
+			Err(WrongChannelErr(self.faucet_channels.clone()))
+		}
+	}
+
+	pub fn has_expanded_limits(&self, roles: &[Id<RoleMarker>]) -> bool {
+		self.expanded_limit_roles
+			.iter()
+			.any(|role| roles.contains(role))
+	}
+}
@@ -0,0 +1,185 @@
+use std::{
+	collections::BTreeMap,
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	time::Duration,
+};
+
+use crate::{
+	chains::{BlockchainListing as _, NativeFaucet as _, SupportedChain},
+	common::GlobalState,
+	metrics::ChainLabels,
+	prelude::*,
+};
+
+/// What `/readyz` reports, outlives restarts of [crate::start::start].
+/// Errors are only logged, as they may contain RPC urls with api keys
+#[derive(Debug, Default)]
+pub struct Health {
+	env_loaded: AtomicBool,
+	salt_initialised: AtomicBool,
+	/// Keyed by shard number, `true` once the shard is ready or resumed
+	shards: Mutex<BTreeMap<u32, bool>>,
+	/// Keyed by chain name, refreshed by [spawn_rpc_checks].
+	/// Only the [orchestration_chain] is required to be ready, as every faucet goes through it,
+	/// while another testnet's RPC being down only affects that chain's faucets
+	rpc_endpoints: Mutex<BTreeMap<String, bool>>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Readiness {
+	pub ready: bool,
+	pub env_loaded: bool,
+	pub salt_initialised: bool,
+	pub shards: BTreeMap<u32, bool>,
+	pub rpc_endpoints: BTreeMap<String, bool>,
+}
+
+impl Health {
+	/// When the bot stops or restarts
+	pub fn reset(&self) {
+		self.env_loaded.store(false, Ordering::Release);
+		self.salt_initialised.store(false, Ordering::Release);
+		self.shards.lock().or_poisoned().clear();
+		self.rpc_endpoints.lock().or_poisoned().clear();
+	}
+
+	pub fn env_loaded(&self) {
+		self.env_loaded.store(true, Ordering::Release);
+	}
+
+	pub fn salt_initialised(&self, ok: bool) {
+		self.salt_initialised.store(ok, Ordering::Release);
+	}
+
+	/// Every shard starts disconnected
+	pub fn expect_shards(&self, shards: impl IntoIterator<Item = u32>) {
+		*self.shards.lock().or_poisoned() =
+			shards.into_iter().map(|shard| (shard, false)).collect();
+	}
+
+	pub fn shard(&self, shard: u32, connected: bool) {
+		self.shards.lock().or_poisoned().insert(shard, connected);
+	}
+
+	pub fn rpc_endpoint(&self, chain: &str, reachable: bool) {
+		self.rpc_endpoints
+			.lock()
+			.or_poisoned()
+			.insert(chain.to_owned(), reachable);
+	}
+
+	pub fn readiness(&self) -> Readiness {
+		let env_loaded = self.env_loaded.load(Ordering::Acquire);
+		let salt_initialised = self.salt_initialised.load(Ordering::Acquire);
+		let shards = self.shards.lock().or_poisoned().clone();
+		let rpc_endpoints = self.rpc_endpoints.lock().or_poisoned().clone();
+		let ready = env_loaded
+			&& salt_initialised
+			&& !shards.is_empty()
+			&& shards.values().all(|connected| *connected)
+			&& rpc_endpoints.get(orchestration_chain()) == Some(&true);
+		Readiness {
+			ready,
+			env_loaded,
+			salt_initialised,
+			shards,
+			rpc_endpoints,
+		}
+	}
+}
+
+/// Salt orchestrates every transaction on Sepolia Arbitrum, whichever chain it's broadcast to
+fn orchestration_chain() -> &'static str {
+	crate::chains::SepoliaArbitrum {
+		address: String::new(),
+	}
+	.chain_name()
+}
+
+/// How often RPC endpoints are checked
+const RPC_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 5);
+
+/// Checks every RPC endpoint is reachable by fetching the vault's balances,
+/// which are also exported as [crate::metrics::Metrics::vault_balance].
+/// Runs until the returned task is aborted
+pub fn spawn_rpc_checks(state: GlobalState) -> tokio::task::JoinHandle<()> {
+	tokio::spawn(async move {
+		let mut interval = tokio::time::interval(RPC_CHECK_INTERVAL);
+		loop {
+			interval.tick().await;
+			let state = state.latest();
+			let state = state.get();
+			for (chain, token, balance) in crate::commands::vault_balances(state.env).await {
+				state.health.rpc_endpoint(chain, balance.is_ok());
+				match balance {
+					Ok(balance) => {
+						state
+							.metrics
+							.vault_balance
+							.get_or_create(&ChainLabels {
+								chain: chain.to_owned(),
+								token: token.to_owned(),
+							})
+							.set(crate::metrics::ether(balance));
+					}
+					Err(err) => warn!(%chain, %token, ?err, "Couldn't fetch the vault balance"),
+				}
+			}
+		}
+	})
+}
+
+/// Initialises the Salt checkout on startup rather than on the first faucet
+pub fn spawn_salt_init(state: GlobalState) -> tokio::task::JoinHandle<()> {
+	tokio::spawn(async move {
+		let state = state.get();
+		let env = state.env;
+		let [chain, ..] = SupportedChain::all();
+		let config = salt_sdk::SaltConfig {
+			private_key: state.private_key.to_hex(),
+			orchestration_network_rpc_node: env.sepolia_arbitrum_rpc_endpoint.clone(),
+			broadcasting_network_rpc_node: chain.rpc_url(env),
+			broadcasting_network_id: chain.chain_id(),
+		};
+		let res = tokio::task::spawn_blocking(move || salt_sdk::Salt::new(config)).await;
+		match res {
+			Ok(Ok(_)) => state.health.salt_initialised(true),
+			Ok(Err(err)) => {
+				error!(?err, "Couldn't initialise the Salt checkout");
+				state.health.salt_initialised(false);
+			}
+			Err(err) => {
+				error!(?err, "Panicked initialising the Salt checkout");
+				state.health.salt_initialised(false);
+			}
+		}
+	})
+}
+
+#[test]
+fn readiness() {
+	let health = Health::default();
+	assert!(!health.readiness().ready);
+
+	health.env_loaded();
+	health.salt_initialised(true);
+	health.expect_shards([0, 1]);
+	health.rpc_endpoint(orchestration_chain(), true);
+	health.shard(0, true);
+	assert!(!health.readiness().ready);
+
+	health.shard(1, true);
+	assert!(health.readiness().ready);
+
+	health.rpc_endpoint("Somnia Shannon", false);
+	assert!(health.readiness().ready);
+
+	health.rpc_endpoint(orchestration_chain(), false);
+	assert!(!health.readiness().ready);
+
+	health.reset();
+	assert!(!health.readiness().ready);
+}
@@ -0,0 +1,79 @@
+use std::net::SocketAddr;
+
+use axum::{
+	Json, Router,
+	extract::State,
+	http::{StatusCode, header},
+	response::IntoResponse,
+	routing::get,
+};
+
+use crate::{health::Health, metrics::Metrics, prelude::*};
+
+/// Optional HTTP server for monitoring, only enabled if `[HTTP]` is configured.
+/// Bind to localhost unless the endpoints should be public
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct HttpConfig {
+	pub listen: SocketAddr,
+}
+
+#[derive(Clone)]
+struct HttpState {
+	health: Arc<Health>,
+	metrics: Arc<Metrics>,
+}
+
+impl HttpConfig {
+	/// Serves until the returned task is aborted.
+	/// Keeps serving while the bot restarts, so supervisors can tell it isn't ready
+	pub fn spawn(&self, health: Arc<Health>, metrics: Arc<Metrics>) -> tokio::task::JoinHandle<()> {
+		let listen = self.listen;
+		tokio::spawn(async move {
+			if let Err(err) = serve(listen, HttpState { health, metrics }).await {
+				error!(?err, %listen, "HTTP server stopped");
+			}
+		})
+	}
+}
+
+async fn serve(listen: SocketAddr, state: HttpState) -> Result<()> {
+	let app = Router::new()
+		.route("/healthz", get(healthz))
+		.route("/readyz", get(readyz))
+		.route("/metrics", get(metrics))
+		.with_state(state);
+	let listener = tokio::net::TcpListener::bind(listen)
+		.await
+		.wrap_err_with(|| format!("Couldn't listen on {listen}"))?;
+	info!(%listen, "Serving HTTP");
+	axum::serve(listener, app)
+		.await
+		.wrap_err("HTTP server failed")
+}
+
+/// The process is alive, even if the bot is restarting
+async fn healthz() -> impl IntoResponse {
+	Json(serde_json::json!({ "alive": true }))
+}
+
+/// 503 until everything in [crate::health::Readiness] is ready
+async fn readyz(State(state): State<HttpState>) -> impl IntoResponse {
+	let readiness = state.health.readiness();
+	let status = if readiness.ready {
+		StatusCode::OK
+	} else {
+		StatusCode::SERVICE_UNAVAILABLE
+	};
+	(status, Json(readiness))
+}
+
+async fn metrics(State(state): State<HttpState>) -> impl IntoResponse {
+	(
+		[(
+			header::CONTENT_TYPE,
+			"application/openmetrics-text; version=1.0.0; charset=utf-8",
+		)],
+		state.metrics.encode(),
+	)
+}
@@ -48,7 +48,22 @@ mod presence {
 	}
 }
 
+mod audit;
+mod cancel;
 mod common;
+mod eligibility;
 pub mod env;
+mod gateway;
+mod guilds;
+mod health;
+mod http;
+mod logs;
+mod metrics;
 mod per_user_spam_filter;
+mod permissions;
 mod ratelimits;
+mod reload;
+mod secrets;
+mod shutdown;
+mod stats;
+mod user_errors;
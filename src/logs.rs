@@ -0,0 +1,172 @@
+use std::io::{Read as _, Write as _};
+
+use camino::Utf8Path;
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+
+use crate::{app_tracing::PREFIX, prelude::*};
+
+/// Where the daily JSON log files are written and how long they're kept
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct LogsConfig {
+	pub dir: Utf8PathBuf,
+	/// Older files are deleted, files are kept forever if unset
+	pub retention_days: Option<u32>,
+	/// Older files are gzipped, files are never compressed if unset
+	pub compress_after_days: Option<u32>,
+}
+
+impl Default for LogsConfig {
+	fn default() -> Self {
+		LogsConfig {
+			dir: crate::app_tracing::LOGS_DIR.into(),
+			retention_days: None,
+			compress_after_days: None,
+		}
+	}
+}
+
+/// One day of logs, named `{PREFIX}.{date}` or `{PREFIX}.{date}.gz` once compressed
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LogFile {
+	pub date: time::Date,
+	pub path: Utf8PathBuf,
+	pub compressed: bool,
+}
+
+const DATE_FORMAT: &[time::format_description::FormatItem<'_>] =
+	time::macros::format_description!("[year]-[month]-[day]");
+
+impl LogFile {
+	fn parse(dir: &Utf8Path, file_name: &str) -> Option<LogFile> {
+		let date = file_name.strip_prefix(PREFIX)?.strip_prefix('.')?;
+		let (date, compressed) = match date.strip_suffix(".gz") {
+			Some(date) => (date, true),
+			None => (date, false),
+		};
+		Some(LogFile {
+			date: time::Date::parse(date, DATE_FORMAT).ok()?,
+			path: dir.join(file_name),
+			compressed,
+		})
+	}
+
+	pub fn date_string(&self) -> String {
+		self.date.format(DATE_FORMAT).unwrap_or_default()
+	}
+
+	/// Decompresses if needed
+	pub async fn read(&self) -> Result<Vec<u8>> {
+		let data = ystd::fs::read(&self.path)
+			.await
+			.wrap_err_with(|| format!("Couldn't read log file {}", self.path))?;
+		if !self.compressed {
+			return Ok(data);
+		}
+		tokio::task::spawn_blocking(move || {
+			let mut decompressed = Vec::new();
+			GzDecoder::new(data.as_slice()).read_to_end(&mut decompressed)?;
+			Ok::<_, std::io::Error>(decompressed)
+		})
+		.await?
+		.wrap_err_with(|| format!("Couldn't decompress log file {}", self.path))
+	}
+
+	async fn compress(&self) -> Result<()> {
+		let data = self.read().await?;
+		let compressed = tokio::task::spawn_blocking(move || {
+			let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+			encoder.write_all(&data)?;
+			encoder.finish()
+		})
+		.await?
+		.wrap_err("Couldn't compress log file")?;
+		let path = Utf8PathBuf::from(format!("{}.gz", self.path));
+		tokio::fs::write(&path, compressed)
+			.await
+			.wrap_err_with(|| format!("Couldn't write compressed log file {path}"))?;
+		tokio::fs::remove_file(&self.path)
+			.await
+			.wrap_err_with(|| format!("Couldn't remove uncompressed log file {}", self.path))?;
+		Ok(())
+	}
+}
+
+impl LogsConfig {
+	/// Sorted oldest first
+	pub async fn files(&self) -> Result<Vec<LogFile>> {
+		let mut files = Vec::new();
+		let mut dir = tokio::fs::read_dir(&self.dir)
+			.await
+			.wrap_err_with(|| format!("Couldn't read logs dir {}", self.dir))?;
+		while let Some(entry) = dir.next_entry().await? {
+			if let Some(file) = LogFile::parse(&self.dir, &entry.file_name().to_string_lossy()) {
+				files.push(file);
+			}
+		}
+		files.sort();
+		Ok(files)
+	}
+
+	/// Inclusive of both ends
+	pub async fn files_between(&self, from: time::Date, to: time::Date) -> Result<Vec<LogFile>> {
+		let mut files = self.files().await?;
+		files.retain(|file| from <= file.date && file.date <= to);
+		Ok(files)
+	}
+
+	/// Compresses and deletes old log files.
+	/// Today's file is never touched, as it is still being written to
+	pub async fn maintain(&self) -> Result<()> {
+		let today = time::OffsetDateTime::now_utc().date();
+		for file in self.files().await? {
+			let age = (today - file.date).whole_days();
+			if age <= 0 {
+				continue;
+			}
+			if let Some(retention_days) = self.retention_days
+				&& age > i64::from(retention_days)
+			{
+				info!(path = %file.path, "Deleting old log file");
+				tokio::fs::remove_file(&file.path)
+					.await
+					.wrap_err_with(|| format!("Couldn't delete old log file {}", file.path))?;
+				continue;
+			}
+			if let Some(compress_after_days) = self.compress_after_days
+				&& age > i64::from(compress_after_days)
+				&& !file.compressed
+			{
+				info!(path = %file.path, "Compressing old log file");
+				file.compress().await?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Runs [LogsConfig::maintain] every few hours until the bot stops
+	pub fn spawn_maintenance(self) -> tokio::task::JoinHandle<()> {
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60 * 6));
+			loop {
+				interval.tick().await;
+				if let Err(err) = self.maintain().await {
+					error!(?err, "Couldn't maintain the log files");
+				}
+			}
+		})
+	}
+}
+
+#[test]
+fn log_file_names() {
+	let dir = Utf8Path::new("/logs");
+	let file = LogFile::parse(dir, "rust-discordbot.json.2025-06-01.gz").expect("to parse");
+	assert!(file.compressed);
+	assert_eq!(file.date_string(), "2025-06-01");
+	assert_eq!(file.path, "/logs/rust-discordbot.json.2025-06-01.gz");
+
+	let file = LogFile::parse(dir, "rust-discordbot.json.2025-06-02").expect("to parse");
+	assert!(!file.compressed);
+	assert!(LogFile::parse(dir, "ratelimits.json").is_none());
+}
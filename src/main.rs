@@ -1,9 +1,23 @@
 #[path = "tracing.rs"]
 mod app_tracing;
 
+use camino::Utf8PathBuf;
+use clap::Parser as _;
+
+#[derive(clap::Parser)]
+struct Cli {
+	/// Path to the config file, see `env.sample.toml`.
+	/// Defaults to `env.toml` in the working directory for release builds
+	#[arg(long)]
+	config: Option<Utf8PathBuf>,
+}
+
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
-	let _guard = app_tracing::install_tracing("info,salt_discord=debug,salt_sdk=debug").await?;
+	let cli = Cli::parse();
+	let logging = app_tracing::install_tracing("info,salt_discord=debug,salt_sdk=debug")?;
+	let env = salt_discordbot::env::Env::load(cli.config.as_deref()).await?;
+	let _guard = logging.to_dir(&env.logs.dir).await?;
 
 	::tracing::info!("Started logging for the discord server");
 
@@ -11,7 +25,7 @@ async fn main() -> color_eyre::Result<()> {
 		.install_default()
 		.expect("Couldn't install default crypto provider");
 
-	salt_discordbot::main().await;
+	salt_discordbot::main(cli.config).await;
 
 	::tracing::info!("Stopping discord server cleanly");
 
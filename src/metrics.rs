@@ -0,0 +1,219 @@
+use std::{sync::atomic::AtomicU64, time::Instant};
+
+use alloy::primitives::{
+	U256,
+	utils::{ParseUnits, Unit},
+};
+use prometheus_client::{
+	encoding::EncodeLabelSet,
+	metrics::{
+		counter::Counter,
+		family::Family,
+		gauge::Gauge,
+		histogram::{Histogram, exponential_buckets},
+	},
+	registry::Registry,
+};
+
+use crate::{prelude::*, stats::Outcome};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct CommandLabels {
+	pub command: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct FaucetLabels {
+	pub chain: String,
+	pub token: String,
+	/// `success` or `failure`
+	pub outcome: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ChainLabels {
+	pub chain: String,
+	pub token: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct StageLabels {
+	pub stage: String,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ShardLabels {
+	pub shard: u32,
+}
+
+/// Prometheus metrics, served at `/metrics` by [crate::http].
+/// Outlives restarts of [crate::start::start]
+pub struct Metrics {
+	registry: Registry,
+	pub interactions: Family<CommandLabels, Counter>,
+	pub faucets: Family<FaucetLabels, Counter>,
+	/// Time between consecutive [salt_sdk::Log]s arriving, labelled by the later log
+	pub salt_stage_latency: Family<StageLabels, Histogram>,
+	pub ratelimit_refusals: Family<ChainLabels, Counter>,
+	pub spam_filter_rejections: Counter,
+	pub gateway_reconnects: Family<ShardLabels, Counter>,
+	/// In ether units, refreshed by [crate::health::spawn_rpc_checks]
+	pub vault_balance: Family<ChainLabels, Gauge<f64, AtomicU64>>,
+}
+
+impl Default for Metrics {
+	fn default() -> Self {
+		let mut registry = Registry::with_prefix("salt_discordbot");
+		let interactions = Family::default();
+		let faucets = Family::default();
+		let salt_stage_latency = Family::<StageLabels, Histogram>::new_with_constructor(|| {
+			// 0.5s to ~4 minutes
+			Histogram::new(exponential_buckets(0.5, 2.0, 10))
+		});
+		let ratelimit_refusals = Family::default();
+		let spam_filter_rejections = Counter::default();
+		let gateway_reconnects = Family::default();
+		let vault_balance = Family::default();
+
+		registry.register(
+			"interactions_received",
+			"Interactions received per command",
+			interactions.clone(),
+		);
+		registry.register(
+			"faucets",
+			"Faucet transactions attempted per chain and token, by outcome",
+			faucets.clone(),
+		);
+		registry.register(
+			"salt_stage_latency_seconds",
+			"Time spent reaching each stage of a Salt transaction",
+			salt_stage_latency.clone(),
+		);
+		registry.register(
+			"ratelimit_refusals",
+			"Faucet requests refused because of ratelimits",
+			ratelimit_refusals.clone(),
+		);
+		registry.register(
+			"spam_filter_rejections",
+			"Faucet requests refused because the user already had one in progress",
+			spam_filter_rejections.clone(),
+		);
+		registry.register(
+			"gateway_reconnects",
+			"Discord gateway connections closed without shutting down",
+			gateway_reconnects.clone(),
+		);
+		registry.register(
+			"vault_balance",
+			"Balance of the faucet's Salt account in ether units",
+			vault_balance.clone(),
+		);
+
+		Metrics {
+			registry,
+			interactions,
+			faucets,
+			salt_stage_latency,
+			ratelimit_refusals,
+			spam_filter_rejections,
+			gateway_reconnects,
+			vault_balance,
+		}
+	}
+}
+
+impl Metrics {
+	/// OpenMetrics text format
+	pub fn encode(&self) -> String {
+		let mut buf = String::new();
+		if let Err(err) = prometheus_client::encoding::text::encode(&mut buf, &self.registry) {
+			error!(%err, "Couldn't encode metrics");
+		}
+		buf
+	}
+
+	pub fn interaction(&self, command: &str) {
+		self.interactions
+			.get_or_create(&CommandLabels {
+				command: command.to_owned(),
+			})
+			.inc();
+	}
+
+	pub fn faucet(&self, chain: &str, token: &str, outcome: &Outcome) {
+		self.faucets
+			.get_or_create(&FaucetLabels {
+				chain: chain.to_owned(),
+				token: token.to_owned(),
+				outcome: outcome.label().to_owned(),
+			})
+			.inc();
+	}
+
+	pub fn gateway_reconnect(&self, shard: u32) {
+		self.gateway_reconnects
+			.get_or_create(&ShardLabels { shard })
+			.inc();
+	}
+
+	pub fn ratelimit_refusal(&self, chain: &str, token: &str) {
+		self.ratelimit_refusals
+			.get_or_create(&ChainLabels {
+				chain: chain.to_owned(),
+				token: token.to_owned(),
+			})
+			.inc();
+	}
+
+	/// Call with each [salt_sdk::Log] as it arrives, `last` is updated to now
+	pub fn salt_stage(&self, log: &salt_sdk::Log, last: &mut Instant) {
+		let stage = match log {
+			salt_sdk::Log::GenericMessage(_) => "message",
+			salt_sdk::Log::BroadcastedTx(_) => "broadcasted_tx",
+			salt_sdk::Log::AutoBroadcasting => "auto_broadcasting",
+			salt_sdk::Log::AutoBroadcastedSuccessfully => "auto_broadcasted",
+			salt_sdk::Log::RobosBroadcastedSuccessfully => "robos_broadcasted",
+		};
+		self.salt_stage_latency
+			.get_or_create(&StageLabels {
+				stage: stage.to_owned(),
+			})
+			.observe(last.elapsed().as_secs_f64());
+		*last = Instant::now();
+	}
+}
+
+/// Lossy, only for display
+pub(crate) fn ether(amount: U256) -> f64 {
+	ParseUnits::from(amount)
+		.format_units(Unit::ETHER)
+		.parse()
+		.unwrap_or(f64::NAN)
+}
+
+#[test]
+fn metrics_encoding() {
+	let metrics = Metrics::default();
+	let success = Outcome::Success {
+		tx_hash: alloy::primitives::TxHash::ZERO,
+	};
+	metrics.faucet("Somnia Shannon", "STT", &success);
+	metrics
+		.vault_balance
+		.get_or_create(&ChainLabels {
+			chain: "Somnia Shannon".into(),
+			token: "STT".into(),
+		})
+		.set(ether(U256::from(1_500_000_000_000_000_000u128)));
+
+	let encoded = metrics.encode();
+	assert!(encoded.contains(
+		r#"salt_discordbot_faucets_total{chain="Somnia Shannon",token="STT",outcome="success"} 1"#
+	));
+	assert!(
+		encoded
+			.contains(r#"salt_discordbot_vault_balance{chain="Somnia Shannon",token="STT"} 1.5"#)
+	);
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use twilight_model::id::{
+	Id,
+	marker::{RoleMarker, UserMarker},
+};
+
+use crate::prelude::*;
+
+/// Who may run which `/admin` subcommands.
+/// Nobody is an admin unless configured
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct AdminPermissions {
+	/// Allowed to run every subcommand without an override
+	pub users: Vec<Id<UserMarker>>,
+	/// Members with any of these roles are allowed to run every subcommand without an override
+	pub roles: Vec<Id<RoleMarker>>,
+	/// Replaces [AdminPermissions::users] and [AdminPermissions::roles] for specific subcommands,
+	/// keyed by subcommand name e.g. `kill` or `ratelimits`
+	pub subcommands: HashMap<String, Allowed>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct Allowed {
+	pub users: Vec<Id<UserMarker>>,
+	pub roles: Vec<Id<RoleMarker>>,
+}
+
+impl Allowed {
+	fn allows(&self, user: Id<UserMarker>, roles: &[Id<RoleMarker>]) -> bool {
+		self.users.contains(&user) || self.roles.iter().any(|role| roles.contains(role))
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("You aren't allowed to use `/admin {subcommand}`")]
+pub struct NotAllowedErr {
+	pub subcommand: String,
+}
+
+impl AdminPermissions {
+	pub fn is_empty(&self) -> bool {
+		self.users.is_empty()
+			&& self.roles.is_empty()
+			&& self
+				.subcommands
+				.values()
+				.all(|allowed| allowed.users.is_empty() && allowed.roles.is_empty())
+	}
+
+	/// `guild_admin_roles` from [crate::guilds::GuildConfig::admin_roles] are treated like
+	/// [AdminPermissions::roles]
+	pub fn check(
+		&self,
+		subcommand: &str,
+		user: Id<UserMarker>,
+		roles: &[Id<RoleMarker>],
+		guild_admin_roles: &[Id<RoleMarker>],
+	) -> Result<(), NotAllowedErr> {
+		let allowed = match self.subcommands.get(subcommand) {
+			Some(allowed) => allowed.allows(user, roles),
+			None => Allowed {
+				users: self.users.clone(),
+				roles: self
+					.roles
+					.iter()
+					.chain(guild_admin_roles)
+					.copied()
+					.collect(),
+			}
+			.allows(user, roles),
+		};
+		if allowed {
+			Ok(())
+		} else {
+			Err(NotAllowedErr {
+				subcommand: subcommand.to_owned(),
+			})
+		}
+	}
+}
+
+#[test]
+fn admin_permissions() {
+	let toml = r##"
+		USERS = [1]
+		ROLES = [10]
+		[SUBCOMMANDS.kill]
+		USERS = [2]
+		"##;
+	let permissions: AdminPermissions = toml::from_str(toml).expect("to deserialize");
+
+	assert!(permissions.check("stats", Id::new(1), &[], &[]).is_ok());
+	assert!(
+		permissions
+			.check("stats", Id::new(3), &[Id::new(10)], &[])
+			.is_ok()
+	);
+	assert!(permissions.check("stats", Id::new(2), &[], &[]).is_err());
+	assert!(
+		permissions
+			.check("stats", Id::new(2), &[Id::new(20)], &[Id::new(20)])
+			.is_ok()
+	);
+	assert!(permissions.check("kill", Id::new(2), &[], &[]).is_ok());
+	assert!(
+		permissions
+			.check("kill", Id::new(1), &[Id::new(10)], &[])
+			.is_err()
+	);
+}
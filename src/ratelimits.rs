@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
-use alloy::primitives::Address;
+use alloy::primitives::{Address, TxHash};
 use std::time::Duration;
 use time::OffsetDateTime;
 use tokio::sync::Mutex;
@@ -12,12 +12,39 @@ use crate::prelude::*;
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 #[serde(try_from = "ser::RateLimits", into = "ser::RateLimits")]
-pub struct RateLimits(HashMap<u64, ChainLimits>);
+pub struct RateLimits {
+	chains: HashMap<u64, ChainLimits>,
+	/// Extra faucets admins have granted, usable on any chain
+	/// once the normal limits are reached
+	grants: HashMap<Id<UserMarker>, u32>,
+	/// Users admins have blocked from the faucet, on top of the configured denylist
+	blocked: BTreeSet<Id<UserMarker>>,
+}
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
 struct ChainLimits {
 	address: HashMap<Address, Vec<OffsetDateTime>>,
 	discord_id: HashMap<Id<UserMarker>, Vec<OffsetDateTime>>,
+	/// Successful faucets and those with an unknown outcome, so users can look up their past transactions
+	#[serde(default)]
+	claims: Vec<Claim>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct Claim {
+	pub discord_id: Id<UserMarker>,
+	pub address: Address,
+	pub token_name: String,
+	pub at: OffsetDateTime,
+	/// [None] if Salt was given up on, see [crate::stats::Outcome::Unknown]
+	pub tx_hash: Option<TxHash>,
+}
+
+/// How many more faucets a user can claim on one chain
+pub struct Allowance {
+	pub remaining: usize,
+	/// [None] if they can claim right now
+	pub next_available: Option<OffsetDateTime>,
 }
 
 #[tokio::test]
@@ -31,90 +58,297 @@ async fn chain_limits_serde() {
 		"##;
 	let _: RateLimits = toml::from_str(toml).expect("to deserialize");
 
+	let toml = r##"
+		grants = { 1234 = 2 }
+		blocked = ["5678"]
+		123 = { address = {}, discord_id = {}}
+		"##;
+	let limits: RateLimits = toml::from_str(toml).expect("to deserialize");
+	assert_eq!(limits.grants.get(&Id::new(1234)), Some(&2));
+	assert!(limits.is_blocked(Id::new(5678)));
+	assert!(limits.chains.contains_key(&123));
+
 	RateLimits::read().await.expect("to deserialize");
 }
 
+#[test]
+fn reset_user_clears_their_addresses() {
+	let (discord_id, address) = (Id::new(1), Address::repeat_byte(1));
+	let mut limits = ChainLimits::default();
+	limits.register(address, discord_id);
+	limits.claims.push(Claim {
+		discord_id,
+		address,
+		token_name: "STT".to_owned(),
+		at: OffsetDateTime::now_utc(),
+		tx_hash: Some(TxHash::ZERO),
+	});
+
+	assert_eq!(limits.reset(Target::User(discord_id)), 2);
+	assert!(limits.address.get(&address).is_none());
+}
+
+#[test]
+fn claims_are_pruned() {
+	let now = OffsetDateTime::now_utc();
+	let claim = |discord_id, at| Claim {
+		discord_id: Id::new(discord_id),
+		address: Address::ZERO,
+		token_name: "STT".to_owned(),
+		at,
+		tx_hash: Some(TxHash::ZERO),
+	};
+	let mut limits = ChainLimits::default();
+	limits
+		.claims
+		.push(claim(2, now - time::Duration::days(365)));
+	for _ in 0..ChainLimits::CLAIMS_KEPT_PER_USER + 5 {
+		limits.claims.push(claim(1, now));
+	}
+	limits.claims.push(claim(3, now));
+
+	limits.prune_claims(&now, Id::new(1));
+	assert_eq!(limits.claims.len(), ChainLimits::CLAIMS_KEPT_PER_USER + 1);
+	assert!(
+		limits
+			.claims
+			.iter()
+			.all(|claim| claim.discord_id != Id::new(2))
+	);
+}
+
 pub struct Key {
 	pub address: Address,
 	pub discord_id: Id<UserMarker>,
 	pub chain_id: u64,
 	pub chain_name: &'static str,
+	pub token_name: &'static str,
+}
+
+/// Who an admin is inspecting or resetting the ratelimits of
+#[derive(Debug, Clone, Copy)]
+pub enum Target {
+	User(Id<UserMarker>),
+	Address(Address),
+}
+
+impl std::fmt::Display for Target {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Target::User(discord_id) => write!(f, "<@{discord_id}>"),
+			Target::Address(address) => write!(f, "{address}"),
+		}
+	}
 }
 
 /// Doesn't use u64 as key
 mod ser {
-	use std::collections::HashMap;
+	use std::collections::{BTreeSet, HashMap};
 
 	use serde::{Deserialize, Serialize};
 
+	use twilight_model::id::{Id, marker::UserMarker};
+
 	use crate::prelude::*;
 
 	#[derive(Serialize, Deserialize)]
-	pub(crate) struct RateLimits(HashMap<String, super::ChainLimits>);
+	pub(crate) struct RateLimits {
+		#[serde(default, skip_serializing_if = "HashMap::is_empty")]
+		grants: HashMap<Id<UserMarker>, u32>,
+		#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+		blocked: BTreeSet<Id<UserMarker>>,
+		#[serde(flatten)]
+		chains: HashMap<String, super::ChainLimits>,
+	}
 
 	impl TryFrom<RateLimits> for super::RateLimits {
 		type Error = color_eyre::Report;
 		fn try_from(value: RateLimits) -> Result<Self, Self::Error> {
-			value
-				.0
+			let chains = value
+				.chains
 				.into_iter()
 				.map(|(k, v)| {
 					let k: u64 = k.parse().wrap_err("Invalid number key")?;
 					Result::<_, Self::Error>::Ok((k, v))
 				})
-				.collect::<Result<_, Self::Error>>()
-				.map(Self)
+				.collect::<Result<_, Self::Error>>()?;
+			Ok(Self {
+				chains,
+				grants: value.grants,
+				blocked: value.blocked,
+			})
 		}
 	}
 
 	impl From<super::RateLimits> for RateLimits {
 		fn from(value: super::RateLimits) -> Self {
-			Self(
-				value
-					.0
+			Self {
+				grants: value.grants,
+				blocked: value.blocked,
+				chains: value
+					.chains
 					.into_iter()
 					.map(|(k, v)| (k.to_string(), v))
 					.collect(),
-			)
+			}
 		}
 	}
 }
 
 impl RateLimits {
+	/// Falls back to any admin granted faucets if the normal limits are reached
 	pub fn check(&mut self, key: &Key) -> Result<(), RateLimitErr> {
-		self.0.entry(key.chain_id).or_default();
-		self.0.get_mut(&key.chain_id).unwrap().check(
+		let res = self.chains.entry(key.chain_id).or_default().check(
 			&OffsetDateTime::now_utc(),
 			key.address,
 			key.discord_id,
 			key.chain_name,
-		)
+		);
+		if res.is_err() && self.remaining_grants(key.discord_id) > 0 {
+			info!(discord_id = %key.discord_id, "Using an admin granted faucet");
+			return Ok(());
+		}
+		res
 	}
 
-	fn describe(&mut self, discord_id: Id<UserMarker>) -> String {
+	pub fn remaining_grants(&self, discord_id: Id<UserMarker>) -> u32 {
+		self.grants.get(&discord_id).copied().unwrap_or_default()
+	}
+
+	pub fn describe(&self, target: Target) -> String {
 		let now = OffsetDateTime::now_utc();
-		let mut ret = String::new();
-		for (chain_id, chain_limits) in &mut self.0 {
-			ret.push_str(&format!("Chain ID: {chain_id}\n"));
-			ret.push_str(&chain_limits.describe(&now, discord_id));
+		let mut ret = format!("Ratelimits for {target}:\n");
+		if let Target::User(discord_id) = target {
+			ret.push_str(&format!(
+				"Admin granted faucets remaining: {}\n",
+				self.remaining_grants(discord_id)
+			));
+			if self.is_blocked(discord_id) {
+				ret.push_str("Blocked from the faucet by an admin\n");
+			}
+		}
+		for (chain_id, chain_limits) in &self.chains {
+			ret.push_str(&format!("Chain ID {chain_id}: "));
+			ret.push_str(&chain_limits.describe(&now, target));
 			ret.push('\n');
 		}
 		ret
 	}
 
-	pub async fn register(&mut self, key: &Key) -> Result<()> {
-		self.0.entry(key.chain_id).or_default();
-		self.0
-			.get_mut(&key.chain_id)
-			.unwrap()
-			.register(key.address, key.discord_id);
+	/// Also for faucets with an unknown outcome, as they may still have been sent
+	pub async fn register(&mut self, key: &Key, tx_hash: Option<TxHash>) -> Result<()> {
+		let chain_limits = self.chains.entry(key.chain_id).or_default();
+		let within_limits = chain_limits
+			.check(
+				&OffsetDateTime::now_utc(),
+				key.address,
+				key.discord_id,
+				key.chain_name,
+			)
+			.is_ok();
+		chain_limits.register(key.address, key.discord_id);
+		chain_limits.claims.push(Claim {
+			discord_id: key.discord_id,
+			address: key.address,
+			token_name: key.token_name.to_owned(),
+			at: OffsetDateTime::now_utc(),
+			tx_hash,
+		});
+		chain_limits.prune_claims(&OffsetDateTime::now_utc(), key.discord_id);
+		if !within_limits && let Some(grants) = self.grants.get_mut(&key.discord_id) {
+			*grants = grants.saturating_sub(1);
+			if *grants == 0 {
+				self.grants.remove(&key.discord_id);
+			}
+		}
 
 		self.save().await?;
 		Ok(())
 	}
 
+	/// Chains that have never been used give the full allowance
+	pub fn allowance(&self, chain_id: u64, discord_id: Id<UserMarker>) -> Allowance {
+		let now = OffsetDateTime::now_utc();
+		match self.chains.get(&chain_id) {
+			Some(chain_limits) => chain_limits.allowance(&now, discord_id),
+			None => Allowance {
+				remaining: ChainLimits::DISCORD_ID_MAX_PER_DAY,
+				next_available: None,
+			},
+		}
+	}
+
+	/// Most recent first
+	pub fn recent_claims(&self, discord_id: Id<UserMarker>, limit: usize) -> Vec<(u64, &Claim)> {
+		let mut claims = self
+			.chains
+			.iter()
+			.flat_map(|(chain_id, chain_limits)| {
+				chain_limits
+					.claims
+					.iter()
+					.filter(move |claim| claim.discord_id == discord_id)
+					.map(|claim| (*chain_id, claim))
+			})
+			.collect::<Vec<_>>();
+		claims.sort_by_key(|(_, claim)| std::cmp::Reverse(claim.at));
+		claims.truncate(limit);
+		claims
+	}
+
+	/// Removes the history of one user, including the addresses they claimed to, or one address,
+	/// on one or all chains. Returns how many records were removed
+	pub async fn reset(&mut self, target: Target, chain_id: Option<u64>) -> Result<usize> {
+		let mut removed = 0;
+		for (id, chain_limits) in &mut self.chains {
+			if chain_id.is_some_and(|chain_id| chain_id != *id) {
+				continue;
+			}
+			removed += chain_limits.reset(target);
+		}
+		info!(%target, ?chain_id, %removed, "Reset ratelimits");
+
+		self.save().await?;
+		Ok(removed)
+	}
+
+	/// Returns the new total of granted faucets for this user
+	pub async fn grant(&mut self, discord_id: Id<UserMarker>, count: u32) -> Result<u32> {
+		let grants = self.grants.entry(discord_id).or_default();
+		*grants = grants.saturating_add(count);
+		let total = *grants;
+		info!(%discord_id, %count, %total, "Granted extra faucets");
+
+		self.save().await?;
+		Ok(total)
+	}
+
+	pub fn is_blocked(&self, discord_id: Id<UserMarker>) -> bool {
+		self.blocked.contains(&discord_id)
+	}
+
+	/// Returns false if they were already blocked
+	pub async fn block(&mut self, discord_id: Id<UserMarker>) -> Result<bool> {
+		let added = self.blocked.insert(discord_id);
+		info!(%discord_id, %added, "Blocked from the faucet");
+
+		self.save().await?;
+		Ok(added)
+	}
+
+	/// Returns false if they weren't blocked
+	pub async fn unblock(&mut self, discord_id: Id<UserMarker>) -> Result<bool> {
+		let removed = self.blocked.remove(&discord_id);
+		info!(%discord_id, %removed, "Unblocked from the faucet");
+
+		self.save().await?;
+		Ok(removed)
+	}
+
+	/// Blocked users stay blocked
 	pub async fn clear(&mut self) -> Result<()> {
-		self.0.clear();
+		self.chains.clear();
+		self.grants.clear();
 		info!(?self, "Purging all ratelimits");
 
 		self.save().await?;
@@ -172,6 +406,32 @@ impl RateLimits {
 }
 
 impl ChainLimits {
+	const ADDRESS_MAX_PER_DAY: usize = 2;
+	const DISCORD_ID_MAX_PER_DAY: usize = 3;
+	/// Claims are kept for this long, and at most [ChainLimits::CLAIMS_KEPT_PER_USER] of them,
+	/// since the whole file is rewritten on every faucet
+	const CLAIM_HISTORY: time::Duration = time::Duration::days(90);
+	const CLAIMS_KEPT_PER_USER: usize = 10;
+
+	/// Drops expired claims, and the oldest of this user's beyond the cap
+	fn prune_claims(&mut self, now: &OffsetDateTime, discord_id: Id<UserMarker>) {
+		self.claims
+			.retain(|claim| *now - claim.at < Self::CLAIM_HISTORY);
+		let mut theirs = self
+			.claims
+			.iter()
+			.filter(|claim| claim.discord_id == discord_id)
+			.count();
+		// claims are pushed in order, so the oldest come first
+		self.claims.retain(|claim| {
+			if claim.discord_id != discord_id || theirs <= Self::CLAIMS_KEPT_PER_USER {
+				return true;
+			}
+			theirs -= 1;
+			false
+		});
+	}
+
 	pub fn check(
 		&mut self,
 		now: &OffsetDateTime,
@@ -214,21 +474,65 @@ impl ChainLimits {
 		}
 	}
 
-	fn describe(&mut self, now: &OffsetDateTime, discord_id: Id<UserMarker>) -> String {
-		let mut ret = String::new();
-		for (address, records) in self.address.clone() {
-			let records = records
-				.into_iter()
-				.map(format_date)
-				.collect::<Vec<String>>();
-			ret.push_str(&format!(
-				"Address (ratelimited: {:?}): {:?}",
-				self.check(now, address, discord_id, "").is_ok(),
-				records
-			));
+	fn describe(&self, now: &OffsetDateTime, target: Target) -> String {
+		let (records, valid) = match target {
+			Target::User(discord_id) => {
+				let records = self
+					.discord_id
+					.get(&discord_id)
+					.cloned()
+					.unwrap_or_default();
+				let valid = Self::discord_id_valid(now, &records);
+				(records, valid)
+			}
+			Target::Address(address) => {
+				let records = self.address.get(&address).cloned().unwrap_or_default();
+				let valid = Self::address_valid(now, &records);
+				(records, valid)
+			}
+		};
+		let records = records
+			.into_iter()
+			.map(format_date)
+			.collect::<Vec<String>>();
+		format!("(ratelimited: {:?}): {:?}", valid.is_err(), records)
+	}
+
+	fn allowance(&self, now: &OffsetDateTime, discord_id: Id<UserMarker>) -> Allowance {
+		let records = self.discord_id.get(&discord_id).map(Vec::as_slice);
+		match Self::discord_id_valid(now, records.unwrap_or_default()) {
+			Ok(records) => Allowance {
+				remaining: Self::DISCORD_ID_MAX_PER_DAY.saturating_sub(records.len()),
+				next_available: None,
+			},
+			Err((diff, _)) => Allowance {
+				remaining: 0,
+				next_available: Some(*now + diff),
+			},
+		}
+	}
+
+	/// A user's reset includes the addresses they claimed to, which would otherwise
+	/// keep them ratelimited. Returns how many records were removed
+	fn reset(&mut self, target: Target) -> usize {
+		let removed =
+			|records: Option<Vec<OffsetDateTime>>| records.map_or(0, |records| records.len());
+		match target {
+			Target::User(discord_id) => {
+				let addresses = self
+					.claims
+					.iter()
+					.filter(|claim| claim.discord_id == discord_id)
+					.map(|claim| claim.address)
+					.collect::<HashSet<_>>();
+				let mut count = removed(self.discord_id.remove(&discord_id));
+				for address in addresses {
+					count += removed(self.address.remove(&address));
+				}
+				count
+			}
+			Target::Address(address) => removed(self.address.remove(&address)),
 		}
-		ret.push('\n');
-		ret
 	}
 
 	/// Automatically saves
@@ -247,9 +551,8 @@ impl ChainLimits {
 		now: &OffsetDateTime,
 		previous: &[OffsetDateTime],
 	) -> Result<Vec<OffsetDateTime>, (time::Duration, Vec<OffsetDateTime>)> {
-		// 2 per day
 		let range = time::Duration::DAY;
-		let max_num_in_range = 2;
+		let max_num_in_range = Self::ADDRESS_MAX_PER_DAY;
 
 		let previous_num = previous
 			.iter()
@@ -268,11 +571,10 @@ impl ChainLimits {
 	/// Return Ok(_) or Err(_) with the relevant datetimes under consideration
 	fn discord_id_valid(
 		now: &OffsetDateTime,
-		previous: &Vec<OffsetDateTime>,
+		previous: &[OffsetDateTime],
 	) -> Result<Vec<OffsetDateTime>, (time::Duration, Vec<OffsetDateTime>)> {
-		// 3 per day
 		let range = time::Duration::DAY;
-		let max_num_in_range = 3;
+		let max_num_in_range = Self::DISCORD_ID_MAX_PER_DAY;
 
 		let previous_num = previous
 			.iter()
@@ -0,0 +1,239 @@
+use std::{collections::BTreeSet, sync::RwLock, time::Duration};
+
+use camino::Utf8Path;
+use tokio::signal::unix::{SignalKind, signal};
+use twilight_http::Client;
+use twilight_model::id::{Id, marker::GuildMarker};
+
+use crate::{audit::Audit, common::GlobalState, env::Env, prelude::*};
+
+/// The latest [Env], swapped atomically by [LiveConfig::reload] without restarting the gateway.
+/// Each interaction takes a snapshot with [GlobalState::latest] when it arrives,
+/// so requests in flight finish with the config they started with
+pub struct LiveConfig {
+	path: Option<Utf8PathBuf>,
+	client: Arc<Client>,
+	latest: RwLock<Snapshot>,
+	/// One reload at a time, so command registrations don't interleave
+	reloading: tokio::sync::Mutex<()>,
+}
+
+/// Everything derived from the config file
+#[derive(Clone)]
+pub(crate) struct Snapshot {
+	pub env: Arc<Env>,
+	/// Audit channels are configured per guild
+	pub audit: Arc<Audit>,
+}
+
+impl Snapshot {
+	pub fn new(client: Arc<Client>, env: Env) -> Snapshot {
+		let audit = Audit::new(
+			client,
+			env.guilds.iter().filter_map(|(guild_id, guild)| {
+				guild
+					.audit_channel
+					.map(|channel| (*guild_id, channel, guild.admin))
+			}),
+		);
+		Snapshot {
+			env: Arc::new(env),
+			audit: Arc::new(audit),
+		}
+	}
+}
+
+impl LiveConfig {
+	/// How often the config file's modification time is checked
+	const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+	pub(crate) fn new(path: Option<Utf8PathBuf>, client: Arc<Client>, snapshot: Snapshot) -> Self {
+		LiveConfig {
+			path,
+			client,
+			latest: RwLock::new(snapshot),
+			reloading: tokio::sync::Mutex::new(()),
+		}
+	}
+
+	pub(crate) fn latest(&self) -> Snapshot {
+		self.latest.read().or_poisoned().clone()
+	}
+
+	fn path(&self) -> Utf8PathBuf {
+		self.path.clone().unwrap_or_else(Env::default_path)
+	}
+
+	/// Validates the config file before swapping it in, keeping the current config if it's invalid.
+	/// Returns a summary of what happened for admins, including admin commands that couldn't be
+	/// (un)registered after the swap
+	pub async fn reload(&self) -> Result<String> {
+		let _reloading = self.reloading.lock().await;
+		let path = self.path();
+		let new = Env::load(self.path.as_deref())
+			.await
+			.wrap_err("The new config is invalid, keeping the current config")?;
+		let old = self.latest().env;
+
+		let mut summary = vec![format!("Reloaded the config from `{path}`")];
+		summary.extend(
+			restart_only_changes(&old, &new)
+				.into_iter()
+				.map(|key| format!("`{key}` changed, which only takes effect after a restart")),
+		);
+
+		let snapshot = Snapshot::new(self.client.clone(), new);
+		let new = snapshot.env.clone();
+		*self.latest.write().or_poisoned() = snapshot;
+		match self.register_admin_commands(&old, &new).await {
+			Ok(registered) => summary.extend(registered),
+			Err(err) => {
+				error!(?err, "Couldn't update the guilds with admin commands");
+				summary.push(format!(
+					"Couldn't update the guilds with admin commands, they'll be updated on restart: {err}"
+				));
+			}
+		}
+		info!(?summary, "Reloaded config");
+		Ok(summary.join("\n"))
+	}
+
+	/// Slash command definitions are static, but which guilds `/admin` is registered in is configured.
+	/// Failures in one guild are logged and summarised without stopping the others
+	async fn register_admin_commands(&self, old: &Env, new: &Env) -> Result<Vec<String>> {
+		let old: BTreeSet<Id<GuildMarker>> = old.admin_guilds().collect();
+		let new: BTreeSet<Id<GuildMarker>> = new.admin_guilds().collect();
+		if old == new {
+			return Ok(Vec::new());
+		}
+
+		let application = self
+			.client
+			.current_user_application()
+			.await?
+			.model()
+			.await?;
+		let interaction_client = self.client.interaction(application.id);
+		let admin_commands = crate::commands::admin_commands();
+		let mut summary = Vec::new();
+		for guild in new.difference(&old) {
+			let res = interaction_client
+				.set_guild_commands(*guild, &admin_commands)
+				.await;
+			summary.push(match res {
+				Ok(_) => format!("Registered admin commands in guild {guild}"),
+				Err(err) => {
+					error!(?err, %guild, "Couldn't register admin commands");
+					format!("Couldn't register admin commands in guild {guild}: {err}")
+				}
+			});
+		}
+		for guild in old.difference(&new) {
+			let res = interaction_client.set_guild_commands(*guild, &[]).await;
+			summary.push(match res {
+				Ok(_) => format!("Removed admin commands from guild {guild}"),
+				Err(err) => {
+					error!(?err, %guild, "Couldn't remove admin commands");
+					format!("Couldn't remove admin commands from guild {guild}: {err}")
+				}
+			});
+		}
+		Ok(summary)
+	}
+
+	/// Reloads on SIGHUP or when the config file is modified.
+	/// Runs until the returned task is aborted
+	pub(crate) fn spawn_watcher(state: GlobalState) -> tokio::task::JoinHandle<()> {
+		tokio::spawn(async move {
+			let config = state.get().config;
+			let path = config.path();
+			let mut hangup = match signal(SignalKind::hangup()) {
+				Ok(hangup) => Some(hangup),
+				Err(err) => {
+					warn!(
+						?err,
+						"Couldn't listen for SIGHUP, only watching the config file"
+					);
+					None
+				}
+			};
+			let mut interval = tokio::time::interval(Self::POLL_INTERVAL);
+			let mut modified = modified(&path).await;
+			loop {
+				let trigger = tokio::select! {
+					Some(()) = async { hangup.as_mut()?.recv().await } => "SIGHUP",
+					_ = interval.tick() => {
+						let now = modified(&path).await;
+						if now == modified {
+							continue;
+						}
+						modified = now;
+						"the config file changing"
+					}
+				};
+				match config.reload().await {
+					Ok(summary) => state
+						.latest()
+						.get()
+						.audit
+						.automatic(format!("Config reloaded because of {trigger}\n{summary}")),
+					Err(err) => {
+						error!(?err, %trigger, "Couldn't reload config");
+						state.latest().get().audit.automatic(format!(
+							"Couldn't reload config because of {trigger}: {err}"
+						));
+					}
+				}
+			}
+		})
+	}
+}
+
+async fn modified(path: &Utf8Path) -> Option<std::time::SystemTime> {
+	tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Keys that are only read when the bot starts
+fn restart_only_changes(old: &Env, new: &Env) -> Vec<&'static str> {
+	let mut changed = Vec::new();
+	if old.bot_token != new.bot_token {
+		changed.push("BOT_TOKEN");
+	}
+	if old.bot_application_id != new.bot_application_id {
+		changed.push("BOT_APPLICATION_ID");
+	}
+	if old.signer != new.signer {
+		changed.push("SIGNER");
+	}
+	if old.logs.dir != new.logs.dir {
+		changed.push("LOGS.DIR");
+	}
+	if old.http.as_ref().map(|http| http.listen) != new.http.as_ref().map(|http| http.listen) {
+		changed.push("HTTP");
+	}
+	changed
+}
+
+#[test]
+fn reload_restart_only_changes() {
+	let env = |token: &str, signer: &str| -> Env {
+		toml::from_str(&format!(
+			r#"
+			BOT_APPLICATION_ID = "1371363785985490975"
+			BOT_TOKEN = "{token}"
+			SOMNIA_SHANNON_RPC_ENDPOINT = "https://dream-rpc.somnia.network/"
+			SEPOLIA_ARBITRUM_RPC_ENDPOINT = "https://sepolia-rollup.arbitrum.io/rpc"
+			SEPOLIA_ETHEREUM_RPC_ENDPOINT = "https://sepolia.drpc.org"
+			POLYGON_AMOY_RPC_ENDPOINT = "https://rpc-amoy.polygon.technology"
+			FAUCET_TESTNET_SALT_ACCOUNT_ADDRESS = "0x33E7fAB0a8a5da1A923180989bD617c9c2D1C493"
+			SIGNER = {{ SOURCE = "ENV", VAR = "{signer}" }}
+			"#
+		))
+		.expect("to deserialize")
+	};
+	assert!(restart_only_changes(&env("a", "PRIVATE_KEY"), &env("a", "PRIVATE_KEY")).is_empty());
+	assert_eq!(
+		restart_only_changes(&env("a", "PRIVATE_KEY"), &env("b", "OTHER_KEY")),
+		vec!["BOT_TOKEN", "SIGNER"]
+	);
+}
@@ -6,12 +6,22 @@ use std::{
 	time::Duration,
 };
 
-use crate::{common::GlobalState, prelude::*};
+use crate::{common::GlobalState, prelude::*, user_errors::CorrelationId};
 use tokio::sync::Notify;
+use tracing::Instrument as _;
 use twilight_gateway::{Event, EventTypeFlags, Shard, StreamExt as _};
-use twilight_model::application::interaction::InteractionData;
+use twilight_model::application::interaction::{
+	InteractionData, InteractionType, application_command::CommandData,
+	message_component::MessageComponentInteractionData,
+};
+
+/// Longer than any Salt transaction should take to be submitted.
+/// Interactions still running by then are aborted, except faucets already submitted to Salt
+/// which finish in the background, see [crate::cancel::Cancellable::is_submitted]
+pub(crate) const INTERACTION_TIMEOUT: Duration = Duration::from_secs(60 * 3);
 
-pub async fn runner(state: GlobalState, mut shard: Shard) {
+/// Returns once the shard closes while shutting down, or closes fatally
+pub async fn runner(state: GlobalState, shard: &mut Shard) {
 	while let Some(item) = shard.next_event(EventTypeFlags::all()).await {
 		let event = match item {
 			Ok(Event::GatewayClose(reason)) => {
@@ -28,10 +38,23 @@ pub async fn runner(state: GlobalState, mut shard: Shard) {
 						"Received a GatewayClose event, but not shutting down: {}",
 						shard.id()
 					);
+					state.get().metrics.gateway_reconnect(shard.id().number());
+					state.get().health.shard(shard.id().number(), false);
 					continue;
 				}
 			}
-			Ok(event) => event,
+			Ok(event) => {
+				match &event {
+					Event::Ready(_) | Event::Resumed => {
+						state.get().health.shard(shard.id().number(), true)
+					}
+					Event::GatewayReconnect | Event::GatewayInvalidateSession(_) => {
+						state.get().health.shard(shard.id().number(), false)
+					}
+					_ => {}
+				}
+				event
+			}
 			Err(error) => {
 				tracing::warn!(?error, "error while receiving event");
 				continue;
@@ -40,15 +63,16 @@ pub async fn runner(state: GlobalState, mut shard: Shard) {
 
 		// Process Discord events
 		tracing::info!(kind = ?event.kind(), shard = ?shard.id().number(), "received event");
-		let state = state.clone();
+		// in flight interactions keep the config they started with
+		let state = state.latest();
 		tokio::spawn(async move {
 			tokio::select! {
 				biased;
-				_ = state.get().kill_now.notified() => {
-					warn!("Automatically cancelling a processing interaction because receiving a shutdown signal");
+				_ = state.get().drain.aborted() => {
+					warn!("Automatically cancelling a processing interaction because shutting down took too long");
 					return;
 				}
-				res = process_interactions(state.clone(), event).timeout(Duration::from_secs(60 * 3))
+				res = process_interactions(state.clone(), event).timeout(INTERACTION_TIMEOUT)
 				 => {
 					if let Err(err) = res {
 						warn!(?err, "Timing out a processing interaction");
@@ -76,14 +100,64 @@ pub async fn process_interactions(state: GlobalState, event: Event) {
 	// Extract the command data from the interaction.
 	// We use mem::take to avoid cloning the data.
 	let data = match core::mem::take(&mut interaction.data) {
-		Some(InteractionData::ApplicationCommand(data)) => *data,
+		Some(InteractionData::ApplicationCommand(data)) => {
+			// suggestions while typing aren't counted as uses, and don't need a span to trace
+			if interaction.kind == InteractionType::ApplicationCommandAutocomplete {
+				if let Err(error) =
+					crate::commands::handle_autocomplete(state, interaction, *data).await
+				{
+					warn!(?error, "error while autocompleting command");
+				}
+				return;
+			}
+			Handled::Command(*data)
+		}
+		Some(InteractionData::MessageComponent(data)) => Handled::Component(*data),
 		_ => {
-			warn!("ignoring non-command interaction");
+			warn!("ignoring unsupported interaction");
 			return;
 		}
 	};
+	let name = match &data {
+		Handled::Command(data) => data.name.as_str(),
+		Handled::Component(data) => crate::commands::component_name(&data.custom_id),
+	}
+	.to_owned();
+
+	state.get().metrics.interaction(&name);
 
-	if let Err(error) = crate::commands::handle_command(state, interaction, data).await {
-		error!(?error, "error while handling command");
+	// fields are searched by `/admin trace`
+	let user = interaction
+		.author_id()
+		.map(|id| id.to_string())
+		.unwrap_or_default();
+	let span = tracing::info_span!(
+		"interaction",
+		interaction_id = %interaction.id,
+		correlation_id = %CorrelationId::of(interaction.id),
+		user = %user,
+		command = %name,
+		tx_hash = tracing::field::Empty,
+	);
+	async move {
+		let res = match data {
+			Handled::Command(data) => {
+				crate::commands::handle_command(state, interaction, data).await
+			}
+			Handled::Component(data) => {
+				crate::commands::handle_component(state, interaction, data).await
+			}
+		};
+		if let Err(error) = res {
+			error!(?error, "error while handling command");
+		}
 	}
+	.instrument(span)
+	.await
+}
+
+/// Button presses are routed like commands
+enum Handled {
+	Command(CommandData),
+	Component(MessageComponentInteractionData),
 }
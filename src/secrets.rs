@@ -0,0 +1,202 @@
+use camino::Utf8Path;
+use zeroize::Zeroizing;
+
+use crate::prelude::*;
+
+/// Where the signer's private key is read from, `[SIGNER]` in env.toml.
+/// Read once on startup by [SecretSource::load]
+#[derive(serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(
+	tag = "SOURCE",
+	rename_all = "SCREAMING_SNAKE_CASE",
+	rename_all_fields = "SCREAMING_SNAKE_CASE"
+)]
+pub enum SecretSource {
+	/// A hex encoded key in an environment variable
+	Env {
+		#[serde(default = "SecretSource::default_var")]
+		var: String,
+	},
+	/// A file containing only the hex encoded key, which mustn't be readable by other users
+	File { path: Utf8PathBuf },
+	/// An encrypted JSON keystore, as created by `cast wallet import` or geth
+	Keystore {
+		path: Utf8PathBuf,
+		#[serde(default)]
+		passphrase: PassphraseSource,
+	},
+}
+
+/// Where a [SecretSource::Keystore]'s passphrase is read from
+#[derive(serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(
+	tag = "SOURCE",
+	rename_all = "SCREAMING_SNAKE_CASE",
+	rename_all_fields = "SCREAMING_SNAKE_CASE"
+)]
+pub enum PassphraseSource {
+	Env { var: String },
+	File { path: Utf8PathBuf },
+}
+
+impl Default for SecretSource {
+	fn default() -> Self {
+		SecretSource::Env {
+			var: SecretSource::default_var(),
+		}
+	}
+}
+
+impl Default for PassphraseSource {
+	fn default() -> Self {
+		PassphraseSource::Env {
+			var: "KEYSTORE_PASSPHRASE".into(),
+		}
+	}
+}
+
+/// Zeroed when dropped and never printed
+pub struct PrivateKey(Zeroizing<[u8; 32]>);
+
+impl std::fmt::Debug for PrivateKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("PrivateKey(redacted)")
+	}
+}
+
+impl PrivateKey {
+	/// Errors never include the key
+	pub fn from_hex(hex: &str) -> Result<PrivateKey> {
+		let hex = hex.trim();
+		let hex = hex.strip_prefix("0x").unwrap_or(hex);
+		let mut key = Zeroizing::new([0u8; 32]);
+		alloy::hex::decode_to_slice(hex, key.as_mut_slice())
+			.map_err(|_| eyre!("Private key isn't a 32 byte hex string"))?;
+		Ok(PrivateKey(key))
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Result<PrivateKey> {
+		let mut key = Zeroizing::new([0u8; 32]);
+		if bytes.len() != key.len() {
+			bail!("Private key is {} bytes long instead of 32", bytes.len());
+		}
+		key.copy_from_slice(bytes);
+		Ok(PrivateKey(key))
+	}
+
+	/// Only call when handing the key to the transaction backend
+	pub fn to_hex(&self) -> Zeroizing<String> {
+		Zeroizing::new(alloy::hex::encode_prefixed(self.0.as_slice()))
+	}
+
+	pub fn signer(&self) -> Result<alloy::signers::local::PrivateKeySigner> {
+		alloy::signers::local::PrivateKeySigner::from_slice(self.0.as_slice())
+			.wrap_err("Private key isn't a valid secp256k1 key")
+	}
+}
+
+impl SecretSource {
+	fn default_var() -> String {
+		"PRIVATE_KEY".into()
+	}
+
+	pub async fn load(&self) -> Result<PrivateKey> {
+		match self {
+			SecretSource::Env { var } => {
+				let hex = Zeroizing::new(
+					std::env::var(var)
+						.wrap_err_with(|| format!("Environment variable {var} isn't set"))?,
+				);
+				PrivateKey::from_hex(&hex)
+					.wrap_err_with(|| format!("From environment variable {var}"))
+			}
+			SecretSource::File { path } => {
+				let hex = read_private_file(path).await?;
+				PrivateKey::from_hex(&hex).wrap_err_with(|| format!("From file {path}"))
+			}
+			SecretSource::Keystore { path, passphrase } => {
+				let passphrase = passphrase.load().await?;
+				let path = path.clone();
+				// scrypt is deliberately slow
+				let key = tokio::task::spawn_blocking(move || {
+					eth_keystore::decrypt_key(&path, passphrase.as_bytes())
+						.map(Zeroizing::new)
+						.wrap_err_with(|| format!("Couldn't decrypt keystore {path}"))
+				})
+				.await??;
+				PrivateKey::from_bytes(&key)
+			}
+		}
+	}
+}
+
+impl PassphraseSource {
+	async fn load(&self) -> Result<Zeroizing<String>> {
+		match self {
+			PassphraseSource::Env { var } => {
+				Ok(Zeroizing::new(std::env::var(var).wrap_err_with(|| {
+					format!("Environment variable {var} isn't set")
+				})?))
+			}
+			PassphraseSource::File { path } => {
+				let passphrase = read_private_file(path).await?;
+				Ok(Zeroizing::new(
+					passphrase.trim_end_matches(['\r', '\n']).to_owned(),
+				))
+			}
+		}
+	}
+}
+
+/// Refuses files that group or other users can access, like ssh does
+async fn read_private_file(path: &Utf8Path) -> Result<Zeroizing<String>> {
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt as _;
+		let mode = tokio::fs::metadata(path)
+			.await
+			.wrap_err_with(|| format!("Couldn't read metadata of {path}"))?
+			.permissions()
+			.mode();
+		if mode & 0o077 != 0 {
+			bail!(
+				"{path} has permissions {:o}, run `chmod 600 {path}` so only its owner can read it",
+				mode & 0o777
+			);
+		}
+	}
+	Ok(Zeroizing::new(
+		tokio::fs::read_to_string(path)
+			.await
+			.wrap_err_with(|| format!("Couldn't read {path}"))?,
+	))
+}
+
+#[test]
+fn private_key_parsing() {
+	let hex = "0x0000000000000000000000000000000000000000000000000000000000000001";
+	let key = PrivateKey::from_hex(hex).expect("to parse");
+	assert_eq!(key.to_hex().as_str(), hex);
+	assert_eq!(format!("{key:?}"), "PrivateKey(redacted)");
+
+	let Err(err) = PrivateKey::from_hex("0xnot a key") else {
+		panic!("an invalid key to be rejected");
+	};
+	assert!(!format!("{err:?}").contains("not a key"));
+
+	let source: SecretSource = toml::from_str(
+		r#"
+		SOURCE = "KEYSTORE"
+		PATH = "keystore.json"
+		PASSPHRASE = { SOURCE = "FILE", PATH = "passphrase" }
+		"#,
+	)
+	.expect("to deserialize");
+	assert!(matches!(
+		source,
+		SecretSource::Keystore {
+			passphrase: PassphraseSource::File { .. },
+			..
+		}
+	));
+}
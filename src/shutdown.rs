@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use crate::prelude::*;
+
+/// Lets faucets in progress finish before shutting down,
+/// instead of dropping them halfway through a Salt transaction
+pub struct Drain {
+	state: watch::Sender<DrainState>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct DrainState {
+	draining: bool,
+	in_flight: usize,
+	/// The deadline passed, anything not submitted to Salt yet is cancelled
+	aborted: bool,
+}
+
+/// Held for as long as a faucet is in progress
+#[must_use]
+pub struct InFlight<'a>(&'a Drain);
+
+impl Drop for InFlight<'_> {
+	fn drop(&mut self) {
+		self.0.state.send_modify(|state| state.in_flight -= 1);
+	}
+}
+
+impl Default for Drain {
+	fn default() -> Self {
+		Drain {
+			state: watch::Sender::new(DrainState::default()),
+		}
+	}
+}
+
+impl Drain {
+	/// The same as the timeout for processing an interaction in [crate::runner],
+	/// so faucets in progress have either finished or been submitted to Salt by then
+	pub const DEADLINE: Duration = crate::runner::INTERACTION_TIMEOUT;
+
+	/// How long faucets submitted to Salt are waited for after [Drain::DEADLINE],
+	/// any still running are abandoned without recording their outcome
+	pub const SUBMITTED_GRACE: Duration = Duration::from_secs(60 * 5);
+
+	/// [None] once draining, so new faucets can be refused
+	pub fn enter(&self) -> Option<InFlight<'_>> {
+		let entered = self.state.send_if_modified(|state| {
+			if state.draining {
+				false
+			} else {
+				state.in_flight += 1;
+				true
+			}
+		});
+		entered.then_some(InFlight(self))
+	}
+
+	pub fn is_draining(&self) -> bool {
+		self.state.borrow().draining
+	}
+
+	/// Stops new faucets then waits up to `deadline` for those in progress to finish.
+	/// Afterwards those not submitted to Salt yet are cancelled, and the rest are waited for
+	/// up to [Drain::SUBMITTED_GRACE].
+	/// Returns how many were still in progress at the deadline
+	pub async fn drain(&self, deadline: Duration) -> usize {
+		self.state.send_modify(|state| state.draining = true);
+		let in_flight = self.state.borrow().in_flight;
+		if in_flight > 0 {
+			info!(%in_flight, ?deadline, "Waiting for faucets in progress to finish");
+		}
+		let mut recv = self.state.subscribe();
+		let finished = tokio::time::timeout(deadline, recv.wait_for(|state| state.in_flight == 0))
+			.await
+			.is_ok();
+		let remaining = self.state.borrow().in_flight;
+		if !finished {
+			warn!(%remaining, "Cancelling faucets not submitted to Salt after the drain deadline");
+		}
+		self.state.send_modify(|state| state.aborted = true);
+		if !finished
+			&& tokio::time::timeout(
+				Self::SUBMITTED_GRACE,
+				recv.wait_for(|state| state.in_flight == 0),
+			)
+			.await
+			.is_err()
+		{
+			let remaining = self.state.borrow().in_flight;
+			error!(%remaining, "Abandoning faucets submitted to Salt that haven't finished");
+		}
+		remaining
+	}
+
+	/// Resolves once [Drain::drain] stops waiting for faucets that weren't submitted to Salt yet
+	pub async fn aborted(&self) {
+		let mut recv = self.state.subscribe();
+		// the sender lives as long as self
+		let _ = recv.wait_for(|state| state.aborted).await;
+	}
+}
+
+#[tokio::test]
+async fn drain_waits_for_in_flight() {
+	let drain = Drain::default();
+	let in_flight = drain.enter().expect("to not be draining yet");
+
+	let waiting = drain.drain(Duration::from_millis(50));
+	tokio::pin!(waiting);
+	tokio::select! {
+		_ = &mut waiting => panic!("drain to wait for the faucet in flight"),
+		_ = tokio::time::sleep(Duration::from_millis(10)) => {}
+	}
+	assert!(drain.is_draining());
+	assert!(drain.enter().is_none());
+
+	drop(in_flight);
+	assert_eq!(waiting.await, 0);
+}
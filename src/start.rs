@@ -1,22 +1,54 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+	sync::atomic::{AtomicBool, Ordering},
+	time::{Duration, Instant},
+};
 
 use crate::{
-	commands::admin_commands, common::GlobalState, env, prelude::*, ratelimits::RateLimits,
+	commands::admin_commands,
+	common::GlobalState,
+	env,
+	gateway::{Backoff, Sessions, Supervisor},
+	health::{self, Health},
+	metrics::Metrics,
+	prelude::*,
+	ratelimits::RateLimits,
+	reload::LiveConfig,
+	shutdown::Drain,
 };
 
+use camino::Utf8Path;
+use color_eyre::Section as _;
 use tokio::sync::Notify;
 use twilight_gateway::{ConfigBuilder, Intents};
 use twilight_http::Client;
-use twilight_model::id::{Id, marker::GuildMarker};
 
-pub async fn main() {
+/// `config_path` is the path passed to [env::Env::load], reloaded on every restart
+pub async fn main(config_path: Option<Utf8PathBuf>) {
 	let keep_restarting = Arc::new(AtomicBool::new(true));
 	let shutting_down = Arc::new(AtomicBool::new(false));
+	let metrics = Arc::new(Metrics::default());
+	let health = Arc::new(Health::default());
+	let sessions = Arc::new(Sessions::read().await);
+	// outlives restarts, so supervisors can see the bot isn't ready
+	let mut http = None;
+	let mut backoff = Backoff::new(Duration::from_secs(2), Duration::from_secs(60 * 5));
 	loop {
 		if !keep_restarting.load(Ordering::Acquire) {
-			return;
+			break;
 		}
-		match start(keep_restarting.clone(), shutting_down.clone()).await {
+		let started = Instant::now();
+		let res = start(
+			config_path.as_deref(),
+			keep_restarting.clone(),
+			shutting_down.clone(),
+			metrics.clone(),
+			health.clone(),
+			sessions.clone(),
+			&mut http,
+		)
+		.await;
+		health.reset();
+		match res {
 			Ok(()) => {
 				// ctrlc, clean exit, actually exit
 				break;
@@ -26,14 +58,50 @@ pub async fn main() {
 				// keep looping
 			}
 		}
-		if !keep_restarting.load(Ordering::Acquire) {
-			tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+		if started.elapsed() > RESTART_HEALTHY_AFTER {
+			backoff.reset();
+		}
+		if keep_restarting.load(Ordering::Acquire) {
+			let delay = backoff.next_delay();
+			info!(?delay, attempt = backoff.attempts(), "Restarting the bot");
+			tokio::time::sleep(delay).await;
 		}
 	}
+	if let Some(http) = http {
+		http.abort();
+	}
 }
 
-pub async fn start(keep_restarting: Arc<AtomicBool>, shutting_down: Arc<AtomicBool>) -> Result<()> {
-	let env = env::Env::get().await?;
+/// Running for longer than this before failing resets the restart backoff
+const RESTART_HEALTHY_AFTER: Duration = Duration::from_secs(60 * 10);
+/// How long the other shards get to close when one gives up, before they're aborted
+const SHARDS_CLOSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn start(
+	config_path: Option<&Utf8Path>,
+	keep_restarting: Arc<AtomicBool>,
+	shutting_down: Arc<AtomicBool>,
+	metrics: Arc<Metrics>,
+	health: Arc<Health>,
+	sessions: Arc<Sessions>,
+	http: &mut Option<tokio::task::JoinHandle<()>>,
+) -> Result<()> {
+	// set while the previous start closed its shards
+	shutting_down.store(false, Ordering::Release);
+	let env = env::Env::load(config_path).await?;
+	// from the first config that loads, so a bad config on startup doesn't hide it for good
+	if http.is_none() {
+		*http = env
+			.http
+			.as_ref()
+			.map(|config| config.spawn(health.clone(), metrics.clone()));
+	}
+	let private_key = env
+		.signer
+		.load()
+		.await
+		.wrap_err("Couldn't load the signer's private key")?;
+	health.env_loaded();
 	let token = env.bot_token.clone();
 	let ratelimits = RateLimits::read().await?;
 
@@ -41,6 +109,9 @@ pub async fn start(keep_restarting: Arc<AtomicBool>, shutting_down: Arc<AtomicBo
 		"Starting discordbot for salt public addresss {}",
 		env.faucet_testnet_salt_account_address
 	);
+	if env.admin.is_empty() {
+		warn!("No admin users or roles are configured, nobody will be able to use /admin");
+	}
 
 	// Initialize Twilight HTTP client and gateway configuration.
 	let client = Arc::new(Client::new(token.clone()));
@@ -74,57 +145,127 @@ pub async fn start(keep_restarting: Arc<AtomicBool>, shutting_down: Arc<AtomicBo
 
 	// Register admin commands
 	let admin_commands = crate::commands::admin_commands();
-	let admin_server: Id<GuildMarker> = Id::new(1371363785985490975);
-	interaction_client
-		.set_guild_commands(admin_server, &admin_commands)
-		.await
-		.wrap_err("Couldn't set admin commands")?;
+	for admin_server in env.admin_guilds() {
+		interaction_client
+			.set_guild_commands(admin_server, &admin_commands)
+			.await
+			.wrap_err("Couldn't set admin commands")
+			.with_note(|| format!("Admin guild: {admin_server}"))?;
+	}
+	if env.admin_guilds().next().is_none() {
+		warn!("No admin guilds are configured, /admin won't be registered anywhere");
+	}
 
-	// Start gateway shards.
-	let shards =
-		twilight_gateway::create_recommended(&client, config, |_id, builder| builder.build())
-			.await?;
+	// Start gateway shards, resuming their previous sessions if possible
+	let supervisor = Arc::new(Supervisor::new(config.clone(), sessions.clone()));
+	let shards = twilight_gateway::create_recommended(&client, config, |id, builder| {
+		sessions.configure(id, builder)
+	})
+	.await?
+	.collect::<Vec<_>>();
 	let shard_len = shards.len();
-	let mut senders = Vec::with_capacity(shard_len);
-	// let mut tasks = Vec::with_capacity(shard_len);
 	let mut tasks = tokio::task::JoinSet::new();
 	let state = GlobalState::new(
 		client,
+		config_path.map(ToOwned::to_owned),
 		env,
+		private_key,
 		ratelimits,
 		Notify::new(),
 		shutting_down.clone(),
+		metrics,
+		health,
 	)?;
 
+	state
+		.get()
+		.audit
+		.automatic(format!("Bot started with {shard_len} shard(s)"));
+	state
+		.get()
+		.health
+		.expect_shards(shards.iter().map(|shard| shard.id().number()));
+	let background = [
+		state.get().env.logs.clone().spawn_maintenance(),
+		health::spawn_rpc_checks(state.clone()),
+		health::spawn_salt_init(state.clone()),
+		LiveConfig::spawn_watcher(state.clone()),
+	];
+
 	for shard in shards {
-		senders.push(shard.sender());
-		tasks.spawn(crate::runner::runner(state.clone(), shard));
+		tasks.spawn(supervisor.clone().supervise(state.clone(), shard));
 	}
 
-	// TODO stop when no shards are left receiving
-	tokio::select! {
+	let gave_up = tokio::select! {
 		res = tokio::signal::ctrl_c() => {
 			debug!(?res, "Ctrl-C has been registered, shutting down");
+			None
 		},
 		_ = state.get().kill_now.notified() => {
 			debug!("Kill request has been listened to, shutting down now");
+			state.get().audit.automatic("Shutting down because of a kill request");
 			keep_restarting.store(false, Ordering::SeqCst);
+			None
 		}
+		Some(res) = tasks.join_next() => Some(res),
 	};
+	if let Some(res) = gave_up {
+		// the other shards' sessions are recorded so they're resumed after restarting
+		shutting_down.store(true, Ordering::Release);
+		supervisor.close_all(twilight_gateway::CloseFrame::RESUME);
+		match tokio::time::timeout(SHARDS_CLOSE_TIMEOUT, tasks.join_all()).await {
+			Ok(results) => {
+				for res in results {
+					if let Err(err) = res {
+						warn!(?err, "Shard stopped with an error while restarting");
+					}
+				}
+			}
+			Err(_) => warn!("The other shards didn't close in time, aborted them"),
+		}
+		for task in background {
+			task.abort();
+		}
+		return match res {
+			Ok(Err(err)) => Err(err),
+			Ok(Ok(())) => Err(eyre!("A shard stopped without shutting down")),
+			Err(err) => Err::<(), _>(err).wrap_err("A shard supervisor panicked"),
+		};
+	}
+	// shards stay connected while draining, so newcomers are told the bot is restarting
+	let remaining = state.get().drain.drain(Drain::DEADLINE).await;
+	if remaining > 0 {
+		state.get().audit.automatic(format!(
+			"{remaining} faucet(s) were still in progress after {}s of shutting down, those not submitted to Salt yet were cancelled and the rest were waited for",
+			Drain::DEADLINE.as_secs()
+		));
+	}
 	shutting_down.store(true, Ordering::Release);
 
-	for sender in senders {
-		// Ignore error if shard's already shutdown.
-		_ = sender.close(twilight_gateway::CloseFrame::NORMAL);
-	}
+	// only killing ends the sessions, otherwise they're resumed when the process restarts
+	let killed = !keep_restarting.load(Ordering::Acquire);
+	supervisor.close_all(if killed {
+		twilight_gateway::CloseFrame::NORMAL
+	} else {
+		twilight_gateway::CloseFrame::RESUME
+	});
 
-	// for jh in tasks {
-	//   _ = jh.await;
-	// }
-	// for res in tasks.join_all().await {
-	//   res?;
-	// }
-	tasks.join_all().await;
+	for res in tasks.join_all().await {
+		if let Err(err) = res {
+			warn!(?err, "Shard stopped with an error while shutting down");
+		}
+	}
+	for task in background {
+		task.abort();
+	}
+	let saved = if killed {
+		sessions.clear().await
+	} else {
+		sessions.save().await
+	};
+	if let Err(err) = saved {
+		warn!(?err, "Couldn't save gateway sessions");
+	}
 
 	Ok(())
 }
@@ -0,0 +1,236 @@
+use std::{
+	collections::{BTreeMap, HashSet},
+	time::Duration,
+};
+
+use alloy::primitives::{
+	Address, TxHash, U256,
+	utils::{ParseUnits, Unit},
+};
+use time::{Date, OffsetDateTime};
+use tokio::io::AsyncWriteExt as _;
+use twilight_model::id::{Id, marker::UserMarker};
+
+use crate::{prelude::*, user_errors::CorrelationId};
+
+/// One attempted faucet transaction, successful or not.
+/// Ratelimit refusals aren't recorded, only attempts that reached Salt
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct FaucetEvent {
+	pub at: OffsetDateTime,
+	pub discord_id: Id<UserMarker>,
+	pub address: Address,
+	pub chain_id: u64,
+	pub chain_name: String,
+	pub token_name: String,
+	/// In the token's smallest unit
+	pub amount: U256,
+	pub outcome: Outcome,
+	/// From the initial response until the transaction finished or failed
+	pub latency: Duration,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub enum Outcome {
+	Success {
+		tx_hash: TxHash,
+	},
+	Failure {
+		reason: String,
+		/// Shown to the user instead of [Outcome::Failure::reason]
+		#[serde(default)]
+		correlation_id: Option<CorrelationId>,
+	},
+	/// Salt was still running after [crate::cancel::Submitted::TIMEOUT],
+	/// the transaction may still have been broadcast
+	Unknown {
+		correlation_id: CorrelationId,
+	},
+}
+
+impl Outcome {
+	/// For [crate::metrics::Metrics::faucet]
+	pub fn label(&self) -> &'static str {
+		match self {
+			Outcome::Success { .. } => "success",
+			Outcome::Failure { .. } => "failure",
+			Outcome::Unknown { .. } => "unknown",
+		}
+	}
+}
+
+/// Simple JSON lines file storage, one [FaucetEvent] per line
+pub struct FaucetEvents;
+
+impl FaucetEvents {
+	const PATH: &str = if cfg!(not(debug_assertions)) {
+		// hard coded for server
+		"/home/ah/Desktop/faucet-events.jsonl"
+	} else {
+		concat!(env!("CARGO_MANIFEST_DIR"), "/faucet-events.jsonl")
+	};
+
+	pub async fn record(event: &FaucetEvent) -> Result<()> {
+		let mut line = serde_json::to_string(event)?;
+		line.push('\n');
+		let mut file = tokio::fs::OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(Self::PATH)
+			.await
+			.wrap_err("Couldn't open faucet events file")?;
+		file.write_all(line.as_bytes()).await?;
+		Ok(())
+	}
+
+	pub async fn read_since(since: OffsetDateTime) -> Result<Vec<FaucetEvent>> {
+		let path = Utf8PathBuf::from(Self::PATH);
+		if !path.is_file().await {
+			return Ok(vec![]);
+		}
+		let file = ystd::fs::read_to_string(&path)
+			.await
+			.wrap_err("Couldn't read faucet events file")?;
+		let mut events = vec![];
+		for line in file.lines().filter(|line| !line.trim().is_empty()) {
+			match serde_json::from_str::<FaucetEvent>(line) {
+				Ok(event) if event.at >= since => events.push(event),
+				Ok(_) => {}
+				Err(err) => warn!(%err, %line, "Skipping unparseable faucet event"),
+			}
+		}
+		Ok(events)
+	}
+}
+
+/// Aggregated per day, chain and token
+#[derive(Debug, Default)]
+pub struct DailyStats {
+	pub attempts: usize,
+	pub failures: usize,
+	/// Only counts successful faucets
+	pub volume: U256,
+	pub users: HashSet<Id<UserMarker>>,
+	pub addresses: HashSet<Address>,
+	latencies: Vec<Duration>,
+}
+
+impl DailyStats {
+	pub fn failure_rate(&self) -> f64 {
+		if self.attempts == 0 {
+			0.0
+		} else {
+			self.failures as f64 / self.attempts as f64
+		}
+	}
+
+	pub fn median_latency(&self) -> Option<Duration> {
+		let mut latencies = self.latencies.clone();
+		latencies.sort();
+		latencies.get(latencies.len() / 2).copied()
+	}
+
+	fn add(&mut self, event: &FaucetEvent) {
+		self.attempts += 1;
+		match event.outcome {
+			Outcome::Success { .. } => self.volume += event.amount,
+			Outcome::Failure { .. } => self.failures += 1,
+			// neither, until someone checks the explorer
+			Outcome::Unknown { .. } => {}
+		}
+		self.users.insert(event.discord_id);
+		self.addresses.insert(event.address);
+		self.latencies.push(event.latency);
+	}
+
+	fn merge(&mut self, other: &DailyStats) {
+		self.attempts += other.attempts;
+		self.failures += other.failures;
+		self.volume += other.volume;
+		self.users.extend(other.users.iter().copied());
+		self.addresses.extend(other.addresses.iter().copied());
+		self.latencies.extend(other.latencies.iter().copied());
+	}
+}
+
+/// (day, chain name, token name)
+pub type StatsKey = (Date, String, String);
+
+pub fn aggregate(events: &[FaucetEvent]) -> BTreeMap<StatsKey, DailyStats> {
+	let mut stats = BTreeMap::<StatsKey, DailyStats>::new();
+	for event in events {
+		let key = (
+			event.at.date(),
+			event.chain_name.clone(),
+			event.token_name.clone(),
+		);
+		stats.entry(key).or_default().add(event);
+	}
+	stats
+}
+
+/// Combines every day into one total per chain and token
+pub fn totals(daily: &BTreeMap<StatsKey, DailyStats>) -> BTreeMap<(String, String), DailyStats> {
+	let mut totals = BTreeMap::<(String, String), DailyStats>::new();
+	for ((_, chain_name, token_name), stats) in daily {
+		totals
+			.entry((chain_name.clone(), token_name.clone()))
+			.or_default()
+			.merge(stats);
+	}
+	totals
+}
+
+pub fn to_csv(daily: &BTreeMap<StatsKey, DailyStats>) -> String {
+	let mut csv = String::from(
+		"date,chain,token,attempts,failures,failure_rate,volume,unique_users,unique_addresses,median_latency_secs\n",
+	);
+	for ((date, chain_name, token_name), stats) in daily {
+		csv.push_str(&format!(
+			"{date},{chain_name},{token_name},{},{},{:.3},{},{},{},{}\n",
+			stats.attempts,
+			stats.failures,
+			stats.failure_rate(),
+			ParseUnits::from(stats.volume).format_units(Unit::ETHER),
+			stats.users.len(),
+			stats.addresses.len(),
+			stats
+				.median_latency()
+				.map(|latency| format!("{:.1}", latency.as_secs_f64()))
+				.unwrap_or_default(),
+		));
+	}
+	csv
+}
+
+#[test]
+fn aggregates_daily_stats() {
+	let event = |discord_id: u64, success: bool, latency: u64| FaucetEvent {
+		at: time::macros::datetime!(2025-06-01 12:00 UTC),
+		discord_id: Id::new(discord_id),
+		address: Address::repeat_byte(discord_id as u8),
+		chain_id: 50312,
+		chain_name: "Somnia Shannon".into(),
+		token_name: "STT".into(),
+		amount: U256::from(10),
+		outcome: if success {
+			Outcome::Success {
+				tx_hash: TxHash::ZERO,
+			}
+		} else {
+			Outcome::Failure {
+				reason: "oops".into(),
+				correlation_id: None,
+			}
+		},
+		latency: Duration::from_secs(latency),
+	};
+	let daily = aggregate(&[event(1, true, 10), event(1, false, 20), event(2, true, 30)]);
+	assert_eq!(daily.len(), 1);
+	let stats = daily.values().next().unwrap();
+	assert_eq!(stats.attempts, 3);
+	assert_eq!(stats.volume, U256::from(20));
+	assert_eq!(stats.users.len(), 2);
+	assert_eq!(stats.median_latency(), Some(Duration::from_secs(20)));
+	assert!(to_csv(&daily).contains("2025-06-01,Somnia Shannon,STT,3,1,0.333"));
+}
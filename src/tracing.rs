@@ -1,9 +1,13 @@
 use camino::Utf8Path;
 use time::{UtcOffset, macros::format_description};
 use tracing_appender::non_blocking::WorkerGuard;
-use tracing_subscriber::fmt::{
-	format::{self, JsonFields},
-	time::OffsetTime,
+use tracing_subscriber::{
+	Layer, Registry,
+	fmt::{
+		format::{self, JsonFields},
+		time::OffsetTime,
+	},
+	reload,
 };
 use ystd::prelude::*;
 
@@ -12,16 +16,24 @@ pub struct Guard {
 	guard: WorkerGuard,
 }
 
+/// Default for `[LOGS] DIR`
 pub const LOGS_DIR: &str = if cfg!(not(debug_assertions)) {
-	// hard coded for server
 	"/home/ah/Desktop/logs"
 } else {
 	concat!(env!("CARGO_MANIFEST_DIR"), "/logs")
 };
 pub const PREFIX: &str = "rust-discordbot.json";
 
+type FileLayer = Option<Box<dyn Layer<Registry> + Send + Sync>>;
+
+/// Logs to stderr until [Logging::to_dir] also starts writing log files
+pub struct Logging {
+	file: reload::Handle<FileLayer, Registry>,
+}
+
+/// Installs color_eyre and logging to stderr, so loading the config can report errors
 #[allow(dead_code)]
-pub async fn install_tracing(filter: &str) -> color_eyre::Result<Guard> {
+pub fn install_tracing(filter: &str) -> color_eyre::Result<Logging> {
 	use tracing_error::ErrorLayer;
 	use tracing_subscriber::prelude::*;
 	use tracing_subscriber::{EnvFilter, fmt};
@@ -34,31 +46,45 @@ pub async fn install_tracing(filter: &str) -> color_eyre::Result<Guard> {
 
 	color_eyre::install()?;
 
-	Utf8PathBuf::from(LOGS_DIR)
-		.assert_dir()
-		.await
-		.wrap_err("Couldn't find logs dir")?;
-
-	let (file, guard) =
-		tracing_appender::non_blocking(tracing_appender::rolling::daily(LOGS_DIR, PREFIX));
-	let file_layer = fmt::layer()
-		.with_ansi(false)
-		.event_format(format::format().json())
-		// https://github.com/tokio-rs/tracing/issues/1365#issuecomment-828845393
-		.fmt_fields(JsonFields::new())
-		.with_writer(file);
-
+	let (file_layer, file) = reload::Layer::new(None);
 	let fmt_layer = fmt::layer().with_target(true);
 	let filter_layer = EnvFilter::try_from_default_env()
 		.or_else(|_| EnvFilter::try_new(filter))
 		.unwrap();
 
 	tracing_subscriber::registry()
-		.with(filter_layer)
 		.with(file_layer)
+		.with(filter_layer)
 		.with(fmt_layer)
 		.with(ErrorLayer::default())
 		.init();
 
-	Ok(Guard { guard })
+	Ok(Logging { file })
+}
+
+impl Logging {
+	/// Also writes JSON logs to daily files in `logs_dir`, once the config says where that is
+	pub async fn to_dir(self, logs_dir: &Utf8Path) -> color_eyre::Result<Guard> {
+		use tracing_subscriber::fmt;
+
+		logs_dir
+			.assert_dir()
+			.await
+			.wrap_err("Couldn't find logs dir")?;
+
+		let (file, guard) =
+			tracing_appender::non_blocking(tracing_appender::rolling::daily(logs_dir, PREFIX));
+		let file_layer = fmt::layer()
+			.with_ansi(false)
+			.event_format(format::format().json())
+			// https://github.com/tokio-rs/tracing/issues/1365#issuecomment-828845393
+			.fmt_fields(JsonFields::new())
+			.with_writer(file)
+			.boxed();
+		self.file
+			.reload(Some(file_layer))
+			.wrap_err("Couldn't start logging to files")?;
+
+		Ok(Guard { guard })
+	}
 }
@@ -0,0 +1,139 @@
+use twilight_model::id::{Id, marker::InteractionMarker};
+
+use crate::prelude::*;
+
+/// Short reference shown to users next to a sanitised error,
+/// so admins can find the full error in the logs.
+/// The interaction's whole snowflake, since its low bits alone are shared across milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+	pub fn of(interaction_id: Id<InteractionMarker>) -> CorrelationId {
+		CorrelationId(interaction_id.get())
+	}
+}
+
+impl std::fmt::Display for CorrelationId {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:016x}", self.0)
+	}
+}
+
+impl std::str::FromStr for CorrelationId {
+	type Err = std::num::ParseIntError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		u64::from_str_radix(s.trim(), 16).map(CorrelationId)
+	}
+}
+
+impl serde::Serialize for CorrelationId {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> serde::Deserialize<'de> for CorrelationId {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		s.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+/// An error that is safe to show in discord.
+/// The full error is only logged, under [UserFacingErr::id]
+#[derive(Debug, thiserror::Error)]
+#[error("{message} (reference `{id}`, share this with an admin if the problem persists)")]
+pub struct UserFacingErr {
+	pub message: &'static str,
+	pub id: CorrelationId,
+}
+
+impl UserFacingErr {
+	/// Logs the full error
+	pub fn salt(id: CorrelationId, err: &salt_sdk::Error) -> UserFacingErr {
+		let message = match err {
+			salt_sdk::Error::SubprocessExitedBadlyWithOutput(output) => {
+				error!(correlation_id = %id, stderr = %output.stderr, stdout = %output.stdout, status = ?output.status, "Salt transaction subprocess failed");
+				classify_revert(&output.stderr).unwrap_or("The transaction failed")
+			}
+			salt_sdk::Error::NoBroadcastedTx | salt_sdk::Error::CouldntConfirmTx(_) => {
+				error!(correlation_id = %id, %err, ?err, "Couldn't confirm a salt transaction");
+				"The transaction couldn't be confirmed, please check the block explorer before trying again"
+			}
+			err => {
+				error!(correlation_id = %id, %err, ?err, "Couldn't run a salt transaction");
+				"The faucet is unavailable right now, please try again later"
+			}
+		};
+		UserFacingErr { message, id }
+	}
+
+	/// Logs the full error
+	pub fn internal(id: CorrelationId, err: &color_eyre::Report) -> UserFacingErr {
+		error!(correlation_id = %id, %err, ?err, "An internal error occurred");
+		UserFacingErr {
+			message: "An internal error occurred",
+			id,
+		}
+	}
+}
+
+/// Matches common RPC and revert errors from the transaction subprocess' stderr.
+/// Only whole words match, so paths, hashes and addresses can't contain a code by accident
+fn classify_revert(stderr: &str) -> Option<&'static str> {
+	let stderr = stderr.to_lowercase();
+	let words: Vec<&str> = stderr
+		.split(|c: char| !c.is_ascii_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.collect();
+	let any = |phrases: &[&str]| {
+		phrases.iter().any(|phrase| {
+			let phrase: Vec<&str> = phrase.split(' ').collect();
+			words.windows(phrase.len()).any(|window| window == phrase)
+		})
+	};
+	if any(&["insufficient funds", "exceeds balance"]) {
+		Some("The faucet has run out of funds on this chain, please try again later")
+	} else if any(&[
+		"nonce too low",
+		"replacement transaction underpriced",
+		"already known",
+	]) {
+		Some("The faucet is busy with another transaction, please try again in a minute")
+	} else if any(&["too many requests", "rate limit", "rate limited", "429"]) {
+		Some("The chain's RPC node is overloaded, please try again later")
+	} else if any(&["timeout", "timed out", "etimedout"]) {
+		Some("The chain took too long to respond, please try again later")
+	} else if any(&["execution reverted"]) {
+		Some("The transaction was rejected by the chain")
+	} else {
+		None
+	}
+}
+
+#[test]
+fn classifies_reverts() {
+	assert_eq!(
+		classify_revert("Error: insufficient funds for gas * price + value"),
+		Some("The faucet has run out of funds on this chain, please try again later")
+	);
+	assert_eq!(
+		classify_revert("code=NONCE_EXPIRED: Nonce too low"),
+		Some("The faucet is busy with another transaction, please try again in a minute")
+	);
+	assert_eq!(
+		classify_revert("HTTP error 429 Too Many Requests"),
+		Some("The chain's RPC node is overloaded, please try again later")
+	);
+	assert_eq!(classify_revert("/home/ah/secret/path.js:12"), None);
+	assert_eq!(classify_revert("sent 0x4291f0 with timeoutMs=5000"), None);
+
+	let id: CorrelationId = "00ab12cd".parse().expect("to parse");
+	assert_eq!(id.to_string(), "0000000000ab12cd");
+	assert_ne!(
+		CorrelationId::of(Id::new(1 << 32 | 7)),
+		CorrelationId::of(Id::new(2 << 32 | 7))
+	);
+}